@@ -1,7 +1,7 @@
+use std::str;
 use std::str::FromStr;
 use std::thread::sleep;
 use std::time::Duration;
-use std::str;
 
 use clap::{crate_authors, crate_version, App, Arg};
 use gpio_lcd::lcd::LcdDriver;
@@ -65,7 +65,7 @@ fn main() -> Result<(), String> {
     let data_pins_res: Vec<Result<u8, std::num::ParseIntError>> = matches
         .values_of("data_pins")
         .unwrap()
-        .map(|p| u8::from_str(p))
+        .map(u8::from_str)
         .collect();
 
     let mut data_pins = Vec::new();
@@ -77,7 +77,7 @@ fn main() -> Result<(), String> {
         data_pins.push(pin_res.as_ref().unwrap());
     });
 
-    let mut lcd = match LcdDriver::new(
+    let lcd = match LcdDriver::new(
         16,
         2,
         matches.value_of("chip").unwrap_or("/dev/gpiochip0"),
@@ -0,0 +1,48 @@
+use std::thread::sleep;
+use std::time::Duration;
+
+use clap::{crate_authors, crate_version, App, Arg};
+use gpio_lcd::lcd::LcdDriver;
+use gpio_lcd::scheduler::{Job, ThreadedLcd};
+use gpio_lcd::terminal::{Rendering, TerminalBus};
+
+fn main() -> Result<(), String> {
+    let matches = App::new("Rust LCD Terminal Test")
+        .author(crate_authors!())
+        .version(crate_version!())
+        .about("Runs the test program against a display drawn in the terminal")
+        .arg(
+            Arg::with_name("pixels")
+                .short("p")
+                .long("pixels")
+                .help("Draws every pixel instead of a character per cell")
+                .takes_value(false),
+        )
+        .get_matches();
+
+    let rendering = if matches.is_present("pixels") {
+        Rendering::Pixels
+    } else {
+        Rendering::Text
+    };
+    let bus = TerminalBus::stdout(16, 2).with_rendering(rendering);
+    let lcd = match LcdDriver::from_bus(bus, 16, 2) {
+        Ok(lcd) => lcd,
+        Err(e) => return Err(format!("{}", e)),
+    };
+
+    let thread_driver = ThreadedLcd::with_driver(lcd);
+    thread_driver.add_job(Job::new(
+        "Test \u{0}\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}",
+        0,
+        Option::from(Duration::from_millis(500)),
+    ));
+    thread_driver.add_job(Job::new(
+        "Scrolling along the bottom row",
+        1,
+        Option::from(Duration::from_millis(300)),
+    ));
+
+    sleep(Duration::from_secs(60 * 60));
+    Ok(())
+}
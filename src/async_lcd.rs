@@ -0,0 +1,652 @@
+use crate::clock::SystemClock;
+use crate::health::{ErrorPolicy, Health, Recovery, WorkerState};
+use crate::lcd::LcdDriver;
+use crate::notify::{Notification, NotificationId};
+use crate::pages::{draw_changes, Page, PageId, Transition};
+use crate::scheduler::{
+    draw_screen, draw_spans, ContentFn, Job, JobHandle, JobId, JobQueue, Shutdown, Signal,
+    Supervisor,
+};
+use gpio_cdev::errors::Error;
+use parking_lot::Mutex;
+use std::io::ErrorKind;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::Handle;
+use tokio::sync::Notify;
+use tokio::task::{self, JoinHandle};
+use tokio::time::{self, Instant};
+
+// The same display as `ThreadedLcd`, driven by a tokio task instead of its own thread. Waits use
+// tokio timers, and dynamic content along with everything that touches the GPIO lines runs on the
+// blocking pool. Nothing holds the queue lock for longer than it takes to change the queue or work
+// out what to draw next, so it's locked directly rather than through an async mutex
+pub struct AsyncLcd {
+    lcd_driver: Arc<Mutex<LcdDriver>>,
+    job_queue: Arc<Mutex<JobQueue>>,
+    job_signal: Arc<Notify>,
+    stopping: Arc<AtomicBool>,
+    supervisor: Arc<Mutex<Supervisor>>,
+    clears: Arc<AtomicU64>,
+    // Taken once the worker has been stopped
+    worker: Option<JoinHandle<()>>,
+    on_drop: Shutdown,
+    cols: u8,
+    rows: u8,
+}
+
+// Everything the worker task shares with the `AsyncLcd`
+struct TaskWorker {
+    lcd_driver: Arc<Mutex<LcdDriver>>,
+    job_queue: Arc<Mutex<JobQueue>>,
+    job_signal: Arc<Notify>,
+    stopping: Arc<AtomicBool>,
+    supervisor: Arc<Mutex<Supervisor>>,
+    // Counts calls to `clear`, a job worked out before one isn't drawn after it
+    clears: Arc<AtomicU64>,
+}
+
+// What the worker does next, worked out with the queue locked
+enum Tick {
+    Wait(Option<Duration>),
+    Transition(Transition, Vec<String>, Vec<String>),
+    Screen(Vec<String>),
+    // The due job's content, which is handed back with its text before the job is drawn
+    Content(JobId, ContentFn),
+    // The job has already been rescheduled, it's put back to run again if drawing fails
+    Job(JobId, u8, Vec<(u8, String)>),
+}
+
+impl AsyncLcd {
+    // Has to be called from inside a tokio runtime, which the worker is spawned on
+    pub fn with_driver(lcd: LcdDriver) -> Self {
+        let (cols, rows) = (lcd.get_cols(), lcd.get_rows());
+        let lcd_driver = Arc::new(Mutex::new(lcd));
+        let job_queue = Arc::new(Mutex::new(JobQueue::new(SystemClock)));
+        let job_signal = Arc::new(Notify::new());
+        let stopping = Arc::new(AtomicBool::new(false));
+        let supervisor = Arc::new(Mutex::new(Supervisor::new()));
+        let clears = Arc::new(AtomicU64::new(0));
+        let worker = TaskWorker {
+            lcd_driver: Arc::clone(&lcd_driver),
+            job_queue: Arc::clone(&job_queue),
+            job_signal: Arc::clone(&job_signal),
+            stopping: Arc::clone(&stopping),
+            supervisor: Arc::clone(&supervisor),
+            clears: Arc::clone(&clears),
+        };
+        AsyncLcd {
+            lcd_driver,
+            job_queue,
+            job_signal,
+            stopping,
+            supervisor,
+            clears,
+            worker: Some(tokio::spawn(worker.run())),
+            on_drop: Shutdown::default(),
+            cols,
+            rows,
+        }
+    }
+
+    // Stop the worker and hand the driver back, the job queue is left as it was
+    pub async fn shutdown(mut self, options: Shutdown) -> Result<LcdDriver, Error> {
+        self.stop();
+        if let Some(worker) = self.worker.take() {
+            // A worker that panicked has already stopped, which is all we need
+            let _ = worker.await;
+            self.supervisor.lock().health.state = WorkerState::Stopped;
+        }
+        let lcd_driver = Arc::clone(&self.lcd_driver);
+        drop(self);
+        let mut lcd_driver = match Arc::try_unwrap(lcd_driver) {
+            Ok(lcd_driver) => lcd_driver.into_inner(),
+            Err(_) => unreachable!("the LCD driver is only shared with the worker"),
+        };
+        task::spawn_blocking(move || {
+            options.apply(&mut lcd_driver)?;
+            Ok(lcd_driver)
+        })
+        .await
+        .map_err(|_| gone())?
+    }
+
+    // How to leave the display if this is dropped without calling `shutdown`. Only done when it's
+    // dropped inside the runtime, which the options are applied on once the worker has finished
+    pub fn on_drop(&mut self, options: Shutdown) {
+        self.on_drop = options;
+    }
+
+    fn stop(&self) {
+        self.stopping.store(true, Ordering::SeqCst);
+        self.job_signal.notify_one();
+    }
+
+    pub fn set_error_policy(&self, policy: ErrorPolicy) {
+        self.supervisor.lock().policy = policy;
+    }
+
+    // The handler runs on the worker task before it backs off, so it shouldn't block
+    pub fn on_error<F>(&self, handler: F)
+    where
+        F: FnMut(&Error) + Send + 'static,
+    {
+        self.supervisor.lock().on_error = Some(Box::new(handler));
+    }
+
+    pub fn health(&self) -> Health {
+        self.supervisor.lock().health.clone()
+    }
+
+    pub async fn add_job(&self, job: Job) -> JobHandle {
+        let id = self.job_queue.lock().push(job);
+        self.job_signal.notify_one();
+        self.handle(id)
+    }
+
+    // The first page added is shown straight away
+    pub async fn add_page(&self, page: Page) -> PageId {
+        let id = self.job_queue.lock().add_page(page);
+        self.job_signal.notify_one();
+        id
+    }
+
+    pub async fn add_page_job(&self, page: PageId, job: Job) -> Option<JobHandle> {
+        let id = self.job_queue.lock().push_to_page(page, job)?;
+        self.job_signal.notify_one();
+        Some(self.handle(id))
+    }
+
+    pub async fn remove_page(&self, page: PageId) -> bool {
+        let removed = self.job_queue.lock().remove_page(page);
+        self.job_signal.notify_one();
+        removed
+    }
+
+    pub async fn show_page(&self, page: PageId) -> bool {
+        let found = self.job_queue.lock().show_page(page);
+        self.job_signal.notify_one();
+        found
+    }
+
+    pub async fn next_page(&self) {
+        self.job_queue.lock().next_page();
+        self.job_signal.notify_one();
+    }
+
+    pub fn current_page(&self) -> Option<PageId> {
+        self.job_queue.lock().active_page()
+    }
+
+    pub async fn set_rotation(&self, interval: Option<Duration>) {
+        self.job_queue.lock().set_rotation(interval);
+        self.job_signal.notify_one();
+    }
+
+    pub async fn set_transition(&self, transition: Transition) {
+        self.job_queue.lock().set_transition(transition);
+    }
+
+    pub async fn notify(&self, notification: Notification) -> NotificationId {
+        let id = self.job_queue.lock().notify(notification);
+        self.job_signal.notify_one();
+        id
+    }
+
+    pub async fn dismiss(&self, id: NotificationId) -> bool {
+        let dismissed = self.job_queue.lock().dismiss(id);
+        self.job_signal.notify_one();
+        dismissed
+    }
+
+    pub async fn clear_jobs(&self) {
+        self.job_queue.lock().clear();
+        self.job_signal.notify_one();
+    }
+
+    pub async fn clear_row(&self, row: u8) {
+        self.job_queue.lock().retain(|job| job.region().row != row);
+        self.add_job(Job::empty(row)).await;
+    }
+
+    // Drops every job and blanks the display
+    pub async fn clear(&self) -> Result<(), Error> {
+        let lcd_driver = Arc::clone(&self.lcd_driver);
+        let job_queue = Arc::clone(&self.job_queue);
+        let clears = Arc::clone(&self.clears);
+        let result = blocking(move || {
+            // The worker checks for a clear with the driver locked before it draws a job
+            let lcd_driver = lcd_driver.lock();
+            job_queue.lock().clear();
+            clears.fetch_add(1, Ordering::SeqCst);
+            lcd_driver.clear()
+        })
+        .await;
+        self.job_signal.notify_one();
+        result
+    }
+
+    pub async fn backlight(&self, on: bool) -> Result<(), Error> {
+        let lcd_driver = Arc::clone(&self.lcd_driver);
+        blocking(move || lcd_driver.lock().backlight(on)).await
+    }
+
+    pub fn get_cols(&self) -> u8 {
+        self.cols
+    }
+
+    pub fn get_rows(&self) -> u8 {
+        self.rows
+    }
+
+    fn handle(&self, id: JobId) -> JobHandle {
+        JobHandle::new(
+            id,
+            Arc::clone(&self.job_queue),
+            Signal::Task(Arc::clone(&self.job_signal)),
+        )
+    }
+}
+
+// The worker can't be waited on here, it finishes by itself once it sees it has been stopped and
+// the `on_drop` options are applied after that on another task
+impl Drop for AsyncLcd {
+    fn drop(&mut self) {
+        if let Some(worker) = self.worker.take() {
+            self.stop();
+            if let Ok(runtime) = Handle::try_current() {
+                let lcd_driver = Arc::clone(&self.lcd_driver);
+                let supervisor = Arc::clone(&self.supervisor);
+                let options = self.on_drop.clone();
+                runtime.spawn(async move {
+                    let _ = worker.await;
+                    supervisor.lock().health.state = WorkerState::Stopped;
+                    let _ = blocking(move || options.apply(&mut lcd_driver.lock())).await;
+                });
+            }
+        }
+    }
+}
+
+impl TaskWorker {
+    async fn run(self) {
+        let (cols, rows) = {
+            let driver = self.lcd_driver.lock();
+            (driver.get_cols(), driver.get_rows())
+        };
+        while !self.stopping.load(Ordering::SeqCst) {
+            // Read with the queue locked, so a clear either comes before the tick or shows up here
+            let (tick, clears) = {
+                let mut job_queue = self.job_queue.lock();
+                (
+                    tick(&mut job_queue, cols, rows),
+                    self.clears.load(Ordering::SeqCst),
+                )
+            };
+            let result = match tick {
+                // A change to the queue wakes us up early, which is fine as everything gets checked
+                // again on the next tick
+                Tick::Wait(None) => {
+                    self.job_signal.notified().await;
+                    continue;
+                }
+                Tick::Wait(Some(wait_time)) => {
+                    let _ = time::timeout(wait_time, self.job_signal.notified()).await;
+                    continue;
+                }
+                Tick::Transition(transition, before, after) => {
+                    self.transition(transition, before, after).await
+                }
+                Tick::Screen(screen) => {
+                    let lcd_driver = Arc::clone(&self.lcd_driver);
+                    blocking(move || draw_screen(&lcd_driver.lock(), &screen)).await
+                }
+                // Content may be slow to fetch or use the queue itself, so it's called without the
+                // queue locked like everything else that might block
+                Tick::Content(id, mut content) => {
+                    let fetched = task::spawn_blocking(move || {
+                        let text = content();
+                        (content, text)
+                    })
+                    .await;
+                    // Content that panicked leaves the job showing the text it had
+                    if let Ok((content, text)) = fetched {
+                        self.job_queue.lock().put_content(id, content, text);
+                    }
+                    continue;
+                }
+                Tick::Job(id, row, spans) => {
+                    let lcd_driver = Arc::clone(&self.lcd_driver);
+                    let worker_clears = Arc::clone(&self.clears);
+                    let result = blocking(move || {
+                        let lcd_driver = lcd_driver.lock();
+                        if worker_clears.load(Ordering::SeqCst) != clears {
+                            return Ok(());
+                        }
+                        draw_spans(&lcd_driver, row, &spans)
+                    })
+                    .await;
+                    if result.is_err() {
+                        self.job_queue.lock().retry_id(id);
+                    }
+                    result
+                }
+            };
+            if !self.check(result, cols, rows).await {
+                break;
+            }
+        }
+    }
+
+    // The steps of the transition are timed by tokio
+    async fn transition(
+        &self,
+        transition: Transition,
+        before: Vec<String>,
+        after: Vec<String>,
+    ) -> Result<(), Error> {
+        let mut last = before;
+        for frame in transition.frames(&last, &after) {
+            let lcd_driver = Arc::clone(&self.lcd_driver);
+            let (shown, drawn) = (last, frame.clone());
+            blocking(move || draw_changes(&lcd_driver.lock(), &shown, &drawn)).await?;
+            time::sleep(transition.step()).await;
+            last = frame;
+        }
+        Ok(())
+    }
+
+    // The same as `ThreadedLcd`'s worker, backing off on a tokio timer
+    async fn check(&self, mut result: Result<(), Error>, cols: u8, rows: u8) -> bool {
+        loop {
+            let backoff = match Supervisor::report(&self.supervisor, result) {
+                None => return true,
+                Some(Recovery::Retry(backoff)) | Some(Recovery::Reinitialize(backoff)) => backoff,
+                Some(Recovery::Stop) => return false,
+            };
+            let until = Instant::now() + backoff;
+            while !self.stopping.load(Ordering::SeqCst) && Instant::now() < until {
+                let _ = time::timeout_at(until, self.job_signal.notified()).await;
+            }
+            let reinitialize = matches!(
+                self.supervisor.lock().policy,
+                ErrorPolicy::Reinitialize { .. }
+            );
+            if !reinitialize || self.stopping.load(Ordering::SeqCst) {
+                return true;
+            }
+            let lcd_driver = Arc::clone(&self.lcd_driver);
+            result = blocking(move || lcd_driver.lock().init()).await;
+            if result.is_ok() {
+                let screen = self.job_queue.lock().screen(cols, rows);
+                let lcd_driver = Arc::clone(&self.lcd_driver);
+                result = blocking(move || draw_screen(&lcd_driver.lock(), &screen)).await;
+            }
+        }
+    }
+}
+
+// One go around the worker loop. Jobs are moved on to their next frame and rescheduled here, so
+// they stay in the queue for their handles while they're being drawn
+fn tick(job_queue: &mut JobQueue, cols: u8, rows: u8) -> Tick {
+    if let Some(screen) = job_queue.notification_update(cols, rows) {
+        return Tick::Screen(screen);
+    }
+    if let Some(page) = job_queue.take_page_switch() {
+        let before = job_queue.compose(cols, rows);
+        job_queue.show_page_now(page);
+        let after = job_queue.compose(cols, rows);
+        return Tick::Transition(job_queue.transition(), before, after);
+    }
+    match job_queue.time_until_next() {
+        Some(wait_time) if wait_time == Duration::from_secs(0) => {
+            if let Some((id, content)) = job_queue.take_due_content() {
+                return Tick::Content(id, content);
+            }
+            match job_queue.pop_due() {
+                Some(mut job) => {
+                    let covering = job_queue.covering(&job);
+                    let (id, row) = (job.id(), job.region().row);
+                    let spans = job.render(cols, &covering);
+                    job_queue.reschedule(job);
+                    Tick::Job(id, row, spans)
+                }
+                // Something is due that can't run yet, whatever changes that wakes us up
+                None => Tick::Wait(None),
+            }
+        }
+        wait_time => Tick::Wait(wait_time),
+    }
+}
+
+async fn blocking<F>(f: F) -> Result<(), Error>
+where
+    F: FnOnce() -> Result<(), Error> + Send + 'static,
+{
+    task::spawn_blocking(f).await.map_err(|_| gone())?
+}
+
+// For when a blocking call panics, the driver may be left part way through a command
+fn gone() -> Error {
+    Error::from(std::io::Error::new(
+        ErrorKind::Interrupted,
+        "LCD driver call panicked",
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::async_lcd::*;
+    use crate::health::WorkerState;
+    use crate::sim::Simulator;
+
+    fn start(simulator: &Simulator) -> AsyncLcd {
+        AsyncLcd::with_driver(LcdDriver::from_bus(simulator.clone(), 8, 2).unwrap())
+    }
+
+    // Waits for the worker to get the display looking like `lines`
+    async fn shows(simulator: &Simulator, lines: &[&str]) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while simulator.controller().lines('?') != lines {
+            assert!(
+                Instant::now() < deadline,
+                "expected {:?}, the display shows {:?}",
+                lines,
+                simulator.controller().lines('?')
+            );
+            time::sleep(Duration::from_millis(1)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn async_job_test() {
+        let simulator = Simulator::new(8, 2);
+        let lcd = start(&simulator);
+        let title = lcd.add_job(Job::new("title", 0, None)).await;
+        lcd.add_job(Job::new("status", 1, Some(Duration::from_millis(10))))
+            .await;
+        shows(&simulator, &["title   ", "status  "]).await;
+
+        // Handles keep working after the job has been drawn
+        assert!(title.set_text("changed"));
+        shows(&simulator, &["changed ", "status  "]).await;
+        lcd.clear_row(1).await;
+        shows(&simulator, &["changed ", "        "]).await;
+        assert!(title.cancel());
+        lcd.shutdown(Shutdown::default()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn async_content_test() {
+        let simulator = Simulator::new(8, 2);
+        let lcd = start(&simulator);
+        let status = lcd.add_job(Job::new("idle", 1, None)).await;
+
+        // Content runs without the queue locked, so it can change other jobs and look at its own
+        let own: Arc<Mutex<Option<JobHandle>>> = Arc::new(Mutex::new(None));
+        let content_own = Arc::clone(&own);
+        let job = Job::dynamic(0, Some(Duration::from_millis(5)), move || {
+            status.set_text("busy");
+            match content_own.lock().as_ref() {
+                Some(own) if own.is_active() => "active".to_string(),
+                Some(_) => "missing".to_string(),
+                None => "waiting".to_string(),
+            }
+        });
+        *own.lock() = Some(lcd.add_job(job).await);
+        shows(&simulator, &["active  ", "busy    "]).await;
+        own.lock().take();
+        lcd.shutdown(Shutdown::default()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn async_clear_test() {
+        let simulator = Simulator::new(8, 2);
+        let lcd = start(&simulator);
+        assert_eq!((lcd.get_cols(), lcd.get_rows()), (8, 2));
+        let title = lcd.add_job(Job::new("title", 0, None)).await;
+        lcd.add_job(Job::new("status", 1, Some(Duration::from_millis(1))))
+            .await;
+        shows(&simulator, &["title   ", "status  "]).await;
+
+        // Nothing is drawn once the display has been cleared, even what the worker had lined up
+        lcd.clear().await.unwrap();
+        assert_eq!(
+            simulator.controller().lines('?'),
+            vec!["        ", "        "]
+        );
+        time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(
+            simulator.controller().lines('?'),
+            vec!["        ", "        "]
+        );
+        assert!(!title.is_active());
+
+        // Without a backlight line there's nothing to switch
+        lcd.backlight(false).await.unwrap();
+        lcd.add_job(Job::new("again", 0, None)).await;
+        shows(&simulator, &["again   ", "        "]).await;
+        lcd.shutdown(Shutdown::default()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn async_drop_test() {
+        let simulator = Simulator::new(8, 2);
+        let mut lcd = start(&simulator);
+        lcd.add_job(Job::new("running", 0, Some(Duration::from_millis(5))))
+            .await;
+        shows(&simulator, &["running ", "        "]).await;
+
+        // Applied once the worker has stopped
+        lcd.on_drop(Shutdown::new().farewell("bye"));
+        drop(lcd);
+        shows(&simulator, &["bye     ", "        "]).await;
+    }
+
+    #[tokio::test]
+    async fn async_page_test() {
+        let simulator = Simulator::new(8, 2);
+        let lcd = start(&simulator);
+        let first = lcd
+            .add_page(Page::new().with_job(Job::new("first", 0, None)))
+            .await;
+        let second = lcd
+            .add_page(Page::new().with_job(Job::new("second", 0, None)))
+            .await;
+        lcd.add_job(Job::new("always", 1, None)).await;
+        shows(&simulator, &["first   ", "always  "]).await;
+        assert_eq!(lcd.current_page(), Some(first));
+
+        assert!(lcd.show_page(second).await);
+        shows(&simulator, &["second  ", "always  "]).await;
+        lcd.next_page().await;
+        shows(&simulator, &["first   ", "always  "]).await;
+        assert!(lcd.remove_page(first).await);
+        shows(&simulator, &["second  ", "always  "]).await;
+        lcd.shutdown(Shutdown::default()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn async_notification_test() {
+        let simulator = Simulator::new(8, 2);
+        let lcd = start(&simulator);
+        lcd.add_job(Job::new("job", 0, None)).await;
+        shows(&simulator, &["job     ", "        "]).await;
+
+        let notification = Notification::new("alert", Duration::from_secs(60));
+        let screen = notification.screen(8, 2, true);
+        let id = lcd.notify(notification).await;
+        shows(&simulator, &[&screen[0], &screen[1]]).await;
+
+        // The job comes back once the notification is dismissed
+        assert!(lcd.dismiss(id).await);
+        shows(&simulator, &["job     ", "        "]).await;
+        assert!(!lcd.dismiss(id).await);
+        lcd.shutdown(Shutdown::default()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn async_error_test() {
+        let simulator = Simulator::new(8, 2);
+        let lcd = start(&simulator);
+        lcd.set_error_policy(ErrorPolicy::Retry {
+            initial: Duration::from_millis(1),
+            max: Duration::from_millis(1),
+        });
+        let handler_simulator = simulator.clone();
+        lcd.on_error(move |_| handler_simulator.set_failing(false));
+
+        // The job that failed is drawn again once the bus is back
+        simulator.set_failing(true);
+        lcd.add_job(Job::new("retried", 0, None)).await;
+        shows(&simulator, &["retried ", "        "]).await;
+        assert_eq!(lcd.health().errors, 1);
+        assert!(lcd.health().is_healthy());
+
+        // Reinitializing blanks the display, the job that already ran is drawn back
+        lcd.set_error_policy(ErrorPolicy::Reinitialize {
+            initial: Duration::from_millis(1),
+            max: Duration::from_millis(1),
+        });
+        simulator.set_failing(true);
+        lcd.add_job(Job::new("again", 1, None)).await;
+        shows(&simulator, &["retried ", "again   "]).await;
+        assert_eq!(lcd.health().errors, 2);
+
+        // With nothing to recover the worker stops
+        lcd.set_error_policy(ErrorPolicy::Stop);
+        lcd.on_error(|_| {});
+        simulator.set_failing(true);
+        lcd.add_job(Job::new("stopped", 1, None)).await;
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while lcd.health().state != WorkerState::Stopped {
+            assert!(Instant::now() < deadline, "the worker never stopped");
+            time::sleep(Duration::from_millis(1)).await;
+        }
+        simulator.set_failing(false);
+        lcd.shutdown(Shutdown::default()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn async_shutdown_test() {
+        let simulator = Simulator::new(8, 2);
+        let lcd = start(&simulator);
+        lcd.add_job(Job::new("running", 0, Some(Duration::from_millis(5))))
+            .await;
+        shows(&simulator, &["running ", "        "]).await;
+        let driver = lcd.shutdown(Shutdown::new().farewell("bye")).await.unwrap();
+        assert_eq!(
+            simulator.controller().lines('?'),
+            vec!["bye     ", "        "]
+        );
+
+        // The worker has let go of the driver, which still works
+        driver.set_cursor(1, 0).unwrap();
+        driver.print("after").unwrap();
+        assert_eq!(
+            simulator.controller().lines('?'),
+            vec!["bye     ", "after   "]
+        );
+    }
+}
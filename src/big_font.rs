@@ -0,0 +1,343 @@
+use crate::lcd::LcdDriver;
+use gpio_cdev::errors::Error;
+
+// Big digits are built out of eight custom segments, so loading a font
+// overwrites every CGRAM slot (including the ones used by `Icon`)
+
+// Segment slots in CGRAM
+const LT: u8 = 0; // Left top corner
+const UB: u8 = 1; // Upper bar
+const RT: u8 = 2; // Right top corner
+const LL: u8 = 3; // Lower left corner
+const LB: u8 = 4; // Lower bar
+const LR: u8 = 5; // Lower right corner
+const UMB: u8 = 6; // Upper and middle bar
+const LMB: u8 = 7; // Lower and middle bar
+
+// Characters from the controller ROM
+const FULL: u8 = 0xFF;
+const BLANK: u8 = 0x20;
+const DOT: u8 = 0xA5; // Centered dot in the A00 ROM
+
+const ROUND_SEGMENTS: [[u8; 8]; 8] = [
+    [0x07, 0x0F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F],
+    [0x1F, 0x1F, 0x1F, 0x00, 0x00, 0x00, 0x00, 0x00],
+    [0x1C, 0x1E, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F],
+    [0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x0F, 0x07],
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x1F, 0x1F, 0x1F],
+    [0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1E, 0x1C],
+    [0x1F, 0x1F, 0x1F, 0x00, 0x00, 0x00, 0x1F, 0x1F],
+    [0x1F, 0x00, 0x00, 0x00, 0x00, 0x1F, 0x1F, 0x1F],
+];
+
+const SQUARE_SEGMENTS: [[u8; 8]; 8] = [
+    [0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F],
+    [0x1F, 0x1F, 0x1F, 0x00, 0x00, 0x00, 0x00, 0x00],
+    [0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F],
+    [0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F],
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x1F, 0x1F, 0x1F],
+    [0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F],
+    [0x1F, 0x1F, 0x1F, 0x00, 0x00, 0x00, 0x1F, 0x1F],
+    [0x1F, 0x00, 0x00, 0x00, 0x00, 0x1F, 0x1F, 0x1F],
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BigStyle {
+    // Two rows tall with rounded corners
+    Round,
+    // Two rows tall with square corners
+    Square,
+    // Four rows tall with rounded corners, for 20x4 panels
+    Tall,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BigFont {
+    style: BigStyle,
+}
+
+impl BigStyle {
+    pub fn height(&self) -> u8 {
+        match *self {
+            BigStyle::Round | BigStyle::Square => 2,
+            BigStyle::Tall => 4,
+        }
+    }
+
+    fn segments(&self) -> &'static [[u8; 8]; 8] {
+        match *self {
+            BigStyle::Round | BigStyle::Tall => &ROUND_SEGMENTS,
+            BigStyle::Square => &SQUARE_SEGMENTS,
+        }
+    }
+
+    // Each row of the returned slice is one row of cells on the display
+    fn layout(&self, c: char) -> &'static [&'static [u8]] {
+        if self.height() == 4 {
+            layout_4(c)
+        } else {
+            layout_2(c)
+        }
+    }
+}
+
+impl BigFont {
+    pub fn new(style: BigStyle) -> Self {
+        BigFont { style }
+    }
+
+    pub fn style(&self) -> BigStyle {
+        self.style
+    }
+
+    pub fn height(&self) -> u8 {
+        self.style.height()
+    }
+
+    // Write the segments for this style into CGRAM, must be done before printing
+    pub fn load(&self, lcd: &LcdDriver) -> Result<(), Error> {
+        for (loc, charmap) in self.style.segments().iter().enumerate() {
+            lcd.create_char(loc as u8, *charmap)?;
+        }
+        Ok(())
+    }
+
+    // Number of columns `text` takes up, including the single column gap between characters.
+    // Stops counting at 255
+    pub fn width(&self, text: &str) -> u8 {
+        let mut width: u8 = 0;
+        for (i, c) in text.chars().enumerate() {
+            if i > 0 {
+                width = width.saturating_add(1);
+            }
+            width = width.saturating_add(self.style.layout(c)[0].len() as u8);
+        }
+        width
+    }
+
+    // Draw `text` with its top left corner at (row, col), anything past the edge of the display is
+    // dropped. Returns the number of columns used
+    pub fn print(&self, lcd: &LcdDriver, text: &str, row: u8, col: u8) -> Result<u8, Error> {
+        let fits = match row.checked_add(self.height()) {
+            Some(bottom) => bottom <= lcd.get_rows(),
+            None => false,
+        };
+        if !fits || col >= lcd.get_cols() {
+            return Err(Error::from(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Big font does not fit on the display",
+            )));
+        }
+        let mut cur_col = col;
+        for (i, c) in text.chars().enumerate() {
+            if i > 0 {
+                for gap_row in row..row + self.height() {
+                    self.print_cells(lcd, &[BLANK], gap_row, cur_col)?;
+                }
+                cur_col = cur_col.saturating_add(1);
+            }
+            cur_col = self.print_char(lcd, c, row, cur_col)?;
+        }
+        Ok(cur_col - col)
+    }
+
+    fn print_char(&self, lcd: &LcdDriver, c: char, row: u8, col: u8) -> Result<u8, Error> {
+        let layout = self.style.layout(c);
+        for (i, cells) in layout.iter().enumerate() {
+            self.print_cells(lcd, cells, row + i as u8, col)?;
+        }
+        Ok(col.saturating_add(layout[0].len() as u8))
+    }
+
+    fn print_cells(&self, lcd: &LcdDriver, cells: &[u8], row: u8, col: u8) -> Result<(), Error> {
+        if col >= lcd.get_cols() {
+            return Ok(());
+        }
+        lcd.set_cursor(row, col)?;
+        for cell in cells.iter().take((lcd.get_cols() - col) as usize) {
+            lcd.write(*cell)?;
+        }
+        Ok(())
+    }
+}
+
+fn layout_2(c: char) -> &'static [&'static [u8]] {
+    match c.to_ascii_uppercase() {
+        '0' => &[&[LT, UB, RT], &[LL, LB, LR]],
+        '1' => &[&[UB, RT, BLANK], &[LB, FULL, LB]],
+        '2' => &[&[UMB, UMB, RT], &[LL, LMB, LMB]],
+        '3' => &[&[UMB, UMB, RT], &[LMB, LMB, LR]],
+        '4' => &[&[LL, LB, FULL], &[BLANK, BLANK, FULL]],
+        '5' => &[&[FULL, UMB, UMB], &[LMB, LMB, LR]],
+        '6' => &[&[LT, UMB, UMB], &[LL, LMB, LR]],
+        '7' => &[&[UB, UB, RT], &[BLANK, BLANK, FULL]],
+        '8' => &[&[LT, UMB, RT], &[LL, LMB, LR]],
+        '9' => &[&[LT, UMB, RT], &[BLANK, BLANK, FULL]],
+        'A' => &[&[LT, UMB, RT], &[FULL, BLANK, FULL]],
+        'C' => &[&[LT, UB, UB], &[LL, LB, LB]],
+        'E' => &[&[FULL, UMB, UMB], &[FULL, LMB, LMB]],
+        'F' => &[&[FULL, UMB, UMB], &[FULL, BLANK, BLANK]],
+        'H' => &[&[FULL, LB, FULL], &[FULL, UB, FULL]],
+        'L' => &[&[FULL, BLANK, BLANK], &[FULL, LB, LB]],
+        'P' => &[&[FULL, UMB, RT], &[FULL, BLANK, BLANK]],
+        'U' => &[&[FULL, BLANK, FULL], &[LL, LB, LR]],
+        '-' => &[&[LB, LB, LB], &[BLANK, BLANK, BLANK]],
+        ':' => &[&[DOT], &[DOT]],
+        '.' => &[&[BLANK], &[LB]],
+        _ => &[&[BLANK, BLANK, BLANK], &[BLANK, BLANK, BLANK]],
+    }
+}
+
+fn layout_4(c: char) -> &'static [&'static [u8]] {
+    match c.to_ascii_uppercase() {
+        '0' => &[
+            &[LT, UB, RT],
+            &[FULL, BLANK, FULL],
+            &[FULL, BLANK, FULL],
+            &[LL, LB, LR],
+        ],
+        '1' => &[
+            &[UB, FULL, BLANK],
+            &[BLANK, FULL, BLANK],
+            &[BLANK, FULL, BLANK],
+            &[LB, FULL, LB],
+        ],
+        '2' => &[&[UB, UB, RT], &[LB, LB, LR], &[LT, UB, UB], &[LL, LB, LB]],
+        '3' => &[&[UB, UB, RT], &[LB, LB, LR], &[UB, UB, RT], &[LB, LB, LR]],
+        '4' => &[
+            &[FULL, BLANK, FULL],
+            &[LL, LB, LR],
+            &[BLANK, BLANK, FULL],
+            &[BLANK, BLANK, FULL],
+        ],
+        '5' => &[&[FULL, UB, UB], &[LL, LB, LB], &[UB, UB, RT], &[LB, LB, LR]],
+        '6' => &[
+            &[LT, UB, UB],
+            &[FULL, LB, LB],
+            &[FULL, UB, RT],
+            &[LL, LB, LR],
+        ],
+        '7' => &[
+            &[UB, UB, RT],
+            &[BLANK, BLANK, FULL],
+            &[BLANK, BLANK, FULL],
+            &[BLANK, BLANK, FULL],
+        ],
+        '8' => &[&[LT, UB, RT], &[LL, LB, LR], &[LT, UB, RT], &[LL, LB, LR]],
+        '9' => &[
+            &[LT, UB, RT],
+            &[LL, LB, LR],
+            &[BLANK, BLANK, FULL],
+            &[LB, LB, LR],
+        ],
+        'A' => &[
+            &[LT, UB, RT],
+            &[FULL, LB, FULL],
+            &[FULL, UB, FULL],
+            &[FULL, BLANK, FULL],
+        ],
+        'C' => &[
+            &[LT, UB, UB],
+            &[FULL, BLANK, BLANK],
+            &[FULL, BLANK, BLANK],
+            &[LL, LB, LB],
+        ],
+        'E' => &[
+            &[FULL, UB, UB],
+            &[FULL, LB, LB],
+            &[FULL, UB, UB],
+            &[FULL, LB, LB],
+        ],
+        'F' => &[
+            &[FULL, UB, UB],
+            &[FULL, LB, LB],
+            &[FULL, UB, UB],
+            &[FULL, BLANK, BLANK],
+        ],
+        'H' => &[
+            &[FULL, BLANK, FULL],
+            &[FULL, LB, FULL],
+            &[FULL, UB, FULL],
+            &[FULL, BLANK, FULL],
+        ],
+        'L' => &[
+            &[FULL, BLANK, BLANK],
+            &[FULL, BLANK, BLANK],
+            &[FULL, BLANK, BLANK],
+            &[FULL, LB, LB],
+        ],
+        'P' => &[
+            &[FULL, UB, RT],
+            &[FULL, LB, LR],
+            &[FULL, BLANK, BLANK],
+            &[FULL, BLANK, BLANK],
+        ],
+        'U' => &[
+            &[FULL, BLANK, FULL],
+            &[FULL, BLANK, FULL],
+            &[FULL, BLANK, FULL],
+            &[LL, LB, LR],
+        ],
+        '-' => &[
+            &[BLANK, BLANK, BLANK],
+            &[LB, LB, LB],
+            &[UB, UB, UB],
+            &[BLANK, BLANK, BLANK],
+        ],
+        ':' => &[&[LB], &[UB], &[LB], &[UB]],
+        '.' => &[&[BLANK], &[BLANK], &[BLANK], &[LB]],
+        _ => &[
+            &[BLANK, BLANK, BLANK],
+            &[BLANK, BLANK, BLANK],
+            &[BLANK, BLANK, BLANK],
+            &[BLANK, BLANK, BLANK],
+        ],
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::big_font::*;
+    use crate::sim::Simulator;
+
+    #[test]
+    fn layouts_are_rectangular_test() {
+        for style in [BigStyle::Round, BigStyle::Square, BigStyle::Tall].iter() {
+            for c in "0123456789ACEFHLPU-:. ?".chars() {
+                let layout = style.layout(c);
+                assert_eq!(layout.len(), style.height() as usize);
+                assert!(layout.iter().all(|row| row.len() == layout[0].len()));
+            }
+        }
+    }
+
+    #[test]
+    fn width_test() {
+        let font = BigFont::new(BigStyle::Round);
+        assert_eq!(font.width(""), 0);
+        assert_eq!(font.width("1"), 3);
+        assert_eq!(font.width("12:34"), 3 + 1 + 3 + 1 + 1 + 1 + 3 + 1 + 3);
+        assert_eq!(font.width(&"8".repeat(100)), 255);
+    }
+
+    #[test]
+    fn print_test() {
+        let simulator = Simulator::new(16, 2);
+        let lcd = LcdDriver::from_bus(simulator.clone(), 16, 2).unwrap();
+        let font = BigFont::new(BigStyle::Round);
+        font.load(&lcd).unwrap();
+
+        // Clipped at the right edge
+        assert_eq!(font.print(&lcd, "12", 0, 12).unwrap(), 7);
+        let controller = simulator.controller();
+        let bottom: Vec<u8> = (12..16).map(|col| controller.code_at(1, col)).collect();
+        assert_eq!(bottom, vec![LB, FULL, LB, BLANK]);
+        drop(controller);
+
+        // Positions off the display are refused rather than wrapping around
+        assert!(font.print(&lcd, "1", 1, 0).is_err());
+        assert!(font.print(&lcd, "1", 255, 0).is_err());
+        assert!(font.print(&lcd, "1", 0, 16).is_err());
+        assert!(font.print(&lcd, "1", 0, 255).is_err());
+    }
+}
@@ -0,0 +1,540 @@
+use std::io::{self, BufRead};
+use std::str::FromStr;
+use std::thread::sleep;
+use std::time::Duration;
+
+use clap::{crate_authors, crate_version, App, AppSettings, Arg, ArgMatches, SubCommand};
+use gpio_cdev::errors::Error;
+use gpio_lcd::config::{self, invalid, number, LcdConfig};
+use gpio_lcd::daemon::{Client, Request, DEFAULT_SOCKET};
+use gpio_lcd::pipe::{Mode, Pipe, Router};
+use gpio_lcd::scheduler::{Job, Shutdown, ThreadedLcd};
+
+fn main() -> Result<(), String> {
+    let matches = app().get_matches();
+    let result = config::from_matches(&matches).and_then(|config| {
+        let daemon = if matches.is_present("daemon") {
+            Some(Client::connect(socket(&config))?)
+        } else {
+            None
+        };
+        match matches.subcommand() {
+            ("init", Some(_)) => init(&config, daemon),
+            ("print", Some(args)) => print(&config, daemon, args),
+            ("clear", Some(_)) => clear(&config, daemon),
+            ("cursor", Some(args)) => cursor(&config, daemon, args),
+            ("char", Some(args)) => custom_char(&config, daemon, args),
+            ("scroll", Some(args)) => scroll(&config, daemon, args),
+            ("backlight", Some(args)) => backlight(&config, daemon, args),
+            ("test-pattern", Some(args)) => test_pattern(&config, daemon, args),
+            ("info", Some(_)) => info(&config, daemon),
+            ("pipe", Some(args)) => pipe(&config, daemon, args),
+            _ => unreachable!("a subcommand is required"),
+        }
+    });
+    result.map_err(|e| format!("{}", e))
+}
+
+fn app() -> App<'static, 'static> {
+    let row = || {
+        Arg::with_name("row")
+            .short("r")
+            .long("row")
+            .value_name("ROW")
+            .help("The row to use, the top one if not given")
+            .takes_value(true)
+    };
+    App::new("lcdctl")
+        .author(crate_authors!())
+        .version(crate_version!())
+        .about("Controls an LCD from the command line, directly or through lcdd")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .args(&config::args())
+        .arg(
+            Arg::with_name("daemon")
+                .short("D")
+                .long("daemon")
+                .help("Go through a running lcdd instead of the GPIO lines")
+                .global(true),
+        )
+        .subcommand(SubCommand::with_name("init").about("Initialises the display, clearing it"))
+        .subcommand(
+            SubCommand::with_name("print")
+                .about("Prints text, leaving the rest of the display as it is")
+                .arg(Arg::with_name("text").required(true))
+                .arg(row())
+                .arg(
+                    Arg::with_name("col")
+                        .long("col")
+                        .value_name("COL")
+                        .help("The column to start at")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("wrap")
+                        .short("w")
+                        .long("wrap")
+                        .help("Carry on onto the next row"),
+                ),
+        )
+        .subcommand(SubCommand::with_name("clear").about("Clears the display"))
+        .subcommand(
+            SubCommand::with_name("cursor")
+                .about("Moves the cursor and sets how it's shown")
+                .arg(Arg::with_name("row").required(true))
+                .arg(Arg::with_name("col").required(true))
+                .arg(
+                    Arg::with_name("style")
+                        .long("style")
+                        .help("How the cursor is shown")
+                        .possible_values(&["off", "underline", "blink", "both"])
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("char")
+                .about("Defines a custom character")
+                .arg(
+                    Arg::with_name("index")
+                        .help("The CGRAM location, 0 to 7. The icons are in 0 to 6")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("pixels")
+                        .help("8 rows from the top, like 01110 or as numbers")
+                        .required(true)
+                        .multiple(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("scroll")
+                .about("Scrolls text along a row")
+                .arg(Arg::with_name("text").required(true))
+                .arg(row())
+                .arg(
+                    Arg::with_name("rate")
+                        .long("rate")
+                        .value_name("MS")
+                        .help("Milliseconds between steps, 300 if not given")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("times")
+                        .long("times")
+                        .value_name("TIMES")
+                        .help("Steps to take before stopping, otherwise it runs until killed")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("backlight")
+                .about("Turns the backlight on or off")
+                .arg(
+                    Arg::with_name("state")
+                        .possible_values(&["on", "off"])
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("test-pattern")
+                .about("Lights every pixel, then shows every character in the ROM")
+                .arg(
+                    Arg::with_name("delay")
+                        .long("delay")
+                        .value_name("MS")
+                        .help("Milliseconds to show each screen for, 1000 if not given")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(SubCommand::with_name("info").about("Shows the display's settings"))
+        .subcommand(
+            SubCommand::with_name("pipe")
+                .about("Shows each line read from stdin, like `some_command | lcdctl pipe`")
+                .arg(
+                    Arg::with_name("route")
+                        .long("route")
+                        .help(
+                            "roll goes down the rows and starts again at the top, tail keeps \
+                             the last lines with the newest at the bottom and prefix puts \
+                             lines like 1:text on the row given. tail if not given",
+                        )
+                        .possible_values(&["roll", "tail", "prefix"])
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("lines")
+                        .long("lines")
+                        .value_name("LINES")
+                        .help("How many rows tail uses, all of them if not given")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("rate")
+                        .long("rate")
+                        .value_name("MS")
+                        .help(
+                            "Milliseconds between scroll steps for long lines, 300 if not given. \
+                             The daemon scrolls at its own rate",
+                        )
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("hold")
+                        .long("hold")
+                        .help("Keep long lines scrolling after the input ends, until killed"),
+                ),
+        )
+}
+
+fn init(config: &LcdConfig, daemon: Option<Client>) -> Result<(), Error> {
+    match daemon {
+        Some(_) => Err(no_daemon("init")),
+        None => config.open().map(|_| ()),
+    }
+}
+
+fn print(config: &LcdConfig, daemon: Option<Client>, args: &ArgMatches) -> Result<(), Error> {
+    let text = args.value_of("text").unwrap();
+    let row = number("row", args.value_of("row").unwrap_or("0"))?;
+    let col: u8 = number("col", args.value_of("col").unwrap_or("0"))?;
+    match daemon {
+        // The daemon owns whole rows
+        Some(mut client) if !args.is_present("wrap") => client
+            .set_row(row, &format!("{}{}", " ".repeat(col as usize), text))
+            .map(|_| ()),
+        Some(_) => Err(no_daemon("print --wrap")),
+        None => {
+            let lcd = config.attach()?;
+            lcd.set_cursor(row, col)?;
+            if args.is_present("wrap") {
+                lcd.print_wrapped(text)
+            } else {
+                lcd.print(text)
+            }
+        }
+    }
+}
+
+fn clear(config: &LcdConfig, daemon: Option<Client>) -> Result<(), Error> {
+    match daemon {
+        Some(mut client) => client.clear(),
+        None => config.attach()?.clear(),
+    }
+}
+
+fn cursor(config: &LcdConfig, daemon: Option<Client>, args: &ArgMatches) -> Result<(), Error> {
+    if daemon.is_some() {
+        return Err(no_daemon("cursor"));
+    }
+    let mut lcd = config.attach()?;
+    lcd.set_cursor(
+        number("row", args.value_of("row").unwrap())?,
+        number("col", args.value_of("col").unwrap())?,
+    )?;
+    match args.value_of("style") {
+        Some("off") => {
+            lcd.no_cursor()?;
+            lcd.no_blink()
+        }
+        Some("underline") => {
+            lcd.cursor()?;
+            lcd.no_blink()
+        }
+        Some("blink") => {
+            lcd.no_cursor()?;
+            lcd.blink()
+        }
+        Some("both") => {
+            lcd.cursor()?;
+            lcd.blink()
+        }
+        _ => Ok(()),
+    }
+}
+
+fn custom_char(config: &LcdConfig, daemon: Option<Client>, args: &ArgMatches) -> Result<(), Error> {
+    if daemon.is_some() {
+        return Err(no_daemon("char"));
+    }
+    let index: u8 = number("index", args.value_of("index").unwrap())?;
+    if index > 7 {
+        return Err(invalid("index should be from 0 to 7".to_string()));
+    }
+    let rows = args
+        .values_of("pixels")
+        .unwrap()
+        .map(pixel_row)
+        .collect::<Result<Vec<u8>, Error>>()?;
+    if rows.len() != 8 {
+        return Err(invalid(format!(
+            "8 rows are needed, {} were given",
+            rows.len()
+        )));
+    }
+    let mut charmap = [0; 8];
+    charmap.copy_from_slice(&rows);
+    config.attach()?.create_char(index, charmap)
+}
+
+fn scroll(config: &LcdConfig, daemon: Option<Client>, args: &ArgMatches) -> Result<(), Error> {
+    let text = args.value_of("text").unwrap();
+    let row = number("row", args.value_of("row").unwrap_or("0"))?;
+    let rate = millis(args, "rate", 300)?;
+    let times = match args.value_of("times") {
+        Some(times) => Some(number::<u32>("times", times)?),
+        None => None,
+    };
+    match daemon {
+        Some(mut client) => {
+            let response = client.request(&Request::AddJob {
+                row,
+                text: text.to_string(),
+                rate_ms: Some(rate.as_millis() as u64),
+                times,
+            })?;
+            if !response.ok {
+                return Err(Error::from(io::Error::other(
+                    response.error.unwrap_or_default(),
+                )));
+            }
+            // The job belongs to this connection, so stay connected while it runs
+            match times {
+                Some(times) => sleep(rate * times),
+                None => loop {
+                    sleep(Duration::from_secs(60 * 60));
+                },
+            }
+            Ok(())
+        }
+        None => {
+            let lcd = ThreadedLcd::with_driver(config.attach()?);
+            let mut job = Job::new(text, row, Some(rate));
+            if let Some(times) = times {
+                job = job.times(times);
+            }
+            let handle = lcd.add_job(job);
+            while handle.is_active() {
+                sleep(rate);
+            }
+            lcd.shutdown(Shutdown::new()).map(|_| ())
+        }
+    }
+}
+
+fn backlight(config: &LcdConfig, daemon: Option<Client>, args: &ArgMatches) -> Result<(), Error> {
+    let on = args.value_of("state") == Some("on");
+    match daemon {
+        Some(mut client) => client.backlight(on),
+        None if config.backlight.is_none() => Err(invalid(
+            "No backlight line given, set it in the config file or with --backlight".to_string(),
+        )),
+        None => config.attach()?.backlight(on),
+    }
+}
+
+fn test_pattern(
+    config: &LcdConfig,
+    daemon: Option<Client>,
+    args: &ArgMatches,
+) -> Result<(), Error> {
+    if daemon.is_some() {
+        return Err(no_daemon("test-pattern"));
+    }
+    let delay = millis(args, "delay", 1000)?;
+    let lcd = config.open()?;
+    let (cols, rows) = (lcd.get_cols(), lcd.get_rows());
+    let show = |codes: &mut dyn Iterator<Item = u8>| -> Result<(), Error> {
+        lcd.clear()?;
+        for row in 0..rows {
+            lcd.set_cursor(row, 0)?;
+            for code in codes.take(cols as usize) {
+                lcd.write(code)?;
+            }
+        }
+        sleep(delay);
+        Ok(())
+    };
+    // Every pixel lit shows up any that are dead
+    show(&mut std::iter::repeat(0xFF))?;
+    // The icons in CGRAM, then the ROM. 0x08 to 0x0F repeat CGRAM and 0x10 to 0x1F are blank
+    // on most ROMs
+    let mut codes = (0x00..0x08).chain(0x20..=0xFF).peekable();
+    while codes.peek().is_some() {
+        show(&mut codes)?;
+    }
+    lcd.clear()
+}
+
+fn info(config: &LcdConfig, daemon: Option<Client>) -> Result<(), Error> {
+    if let Some(mut client) = daemon {
+        let (cols, rows) = client.size()?;
+        println!("Daemon     {}", socket(config));
+        println!("Display    {}x{}", cols, rows);
+        return Ok(());
+    }
+    let line = |line: Option<u8>| line.map_or("-".to_string(), |line| line.to_string());
+    println!("Display    {}x{}", config.cols, config.rows);
+    println!("Interface  {} bit", if config.four_bit { 4 } else { 8 });
+    println!("ROM        {:?}", config.rom);
+    println!("Chip       {}", config.chip);
+    println!(
+        "Lines      rs {}, rw {}, enable {}, data {}, backlight {}",
+        line(config.rs),
+        line(config.rw),
+        line(config.enable),
+        config
+            .data
+            .iter()
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>()
+            .join(" "),
+        line(config.backlight)
+    );
+    Ok(())
+}
+
+fn pipe(config: &LcdConfig, daemon: Option<Client>, args: &ArgMatches) -> Result<(), Error> {
+    let lines = match args.value_of("lines") {
+        Some(lines) => number("lines", lines)?,
+        None => u8::MAX,
+    };
+    let mode = match args.value_of("route") {
+        Some("roll") => Mode::Roll,
+        Some("prefix") => Mode::Prefix,
+        _ => Mode::Tail(lines),
+    };
+    if daemon.is_some() && args.is_present("rate") {
+        return Err(no_daemon("pipe --rate"));
+    }
+    let rate = millis(args, "rate", 300)?;
+    let stdin = io::stdin();
+    match daemon {
+        // Text set on a row scrolls by itself on the daemon
+        Some(mut client) => {
+            let mut router = Router::new(mode, client.size()?.1);
+            for line in stdin.lock().lines() {
+                for (row, text) in router.route(&line?) {
+                    client.set_row(row, &text)?;
+                }
+            }
+            Ok(())
+        }
+        None => {
+            let lcd = ThreadedLcd::with_driver(config.attach()?);
+            let mut pipe = Pipe::new(&lcd, mode).with_rate(rate);
+            pipe.read(stdin.lock())?;
+            if args.is_present("hold") {
+                loop {
+                    sleep(Duration::from_secs(60 * 60));
+                }
+            }
+            pipe.flush();
+            lcd.shutdown(Shutdown::new()).map(|_| ())
+        }
+    }
+}
+
+fn socket(config: &LcdConfig) -> &str {
+    config.socket.as_deref().unwrap_or(DEFAULT_SOCKET)
+}
+
+// A row of a custom character, `01110` or a number like `14` or `0x0E`
+fn pixel_row(row: &str) -> Result<u8, Error> {
+    let parsed = if row.len() == 5 && row.chars().all(|c| c == '0' || c == '1') {
+        u8::from_str_radix(row, 2).ok()
+    } else if let Some(hex) = row.strip_prefix("0x") {
+        u8::from_str_radix(hex, 16).ok()
+    } else {
+        u8::from_str(row).ok()
+    };
+    match parsed {
+        Some(bits) if bits < 0x20 => Ok(bits),
+        _ => Err(invalid(format!(
+            "{} isn't a row of 5 pixels, like 01110 or 0x0E",
+            row
+        ))),
+    }
+}
+
+// An argument given in milliseconds
+fn millis(args: &ArgMatches, name: &str, default: u64) -> Result<Duration, Error> {
+    let millis = match args.value_of(name) {
+        Some(value) => number(name, value)?,
+        None => default,
+    };
+    Ok(Duration::from_millis(millis))
+}
+
+fn no_daemon(command: &str) -> Error {
+    invalid(format!(
+        "{} can't go through the daemon, leave out --daemon",
+        command
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    // Parses a command line with an empty config file, so one in /etc doesn't get in the way
+    fn parse(args: &[&str]) -> Result<(LcdConfig, ArgMatches<'static>), String> {
+        let args = ["lcdctl", "-C", "/dev/null"].iter().chain(args);
+        let matches = app()
+            .get_matches_from_safe(args)
+            .map_err(|e| e.to_string())?;
+        let config = config::from_matches(&matches).map_err(|e| e.to_string())?;
+        Ok((config, matches))
+    }
+
+    #[test]
+    fn pixel_row_test() {
+        for row in &["01110", "14", "0x0E"] {
+            assert_eq!(pixel_row(row).unwrap(), 0x0E);
+        }
+        assert_eq!(pixel_row("11111").unwrap(), 0x1F);
+        assert_eq!(pixel_row("00000").unwrap(), 0);
+        // Five pixels at most, and a row that isn't five digits is a number
+        assert!(pixel_row("32").is_err());
+        assert!(pixel_row("0x20").is_err());
+        assert!(pixel_row("0111").is_err());
+        assert!(pixel_row("0110x").is_err());
+        assert!(pixel_row("").is_err());
+    }
+
+    #[test]
+    fn args_test() {
+        let (config, matches) = parse(&[
+            "scroll", "moving", "-r", "1", "--rate", "100", "--cols", "20",
+        ])
+        .unwrap();
+        assert_eq!((config.cols, config.rows), (20, 2));
+        let args = match matches.subcommand() {
+            ("scroll", Some(args)) => args,
+            other => panic!("parsed as {:?}", other.0),
+        };
+        assert_eq!(args.value_of("text"), Some("moving"));
+        assert_eq!(
+            number::<u8>("row", args.value_of("row").unwrap()).unwrap(),
+            1
+        );
+        assert_eq!(
+            millis(args, "rate", 300).unwrap(),
+            Duration::from_millis(100)
+        );
+        assert_eq!(millis(args, "times", 5).unwrap(), Duration::from_millis(5));
+
+        let (_, matches) = parse(&["char", "3", "01110", "0x1F", "4"]).unwrap();
+        let args = matches.subcommand_matches("char").unwrap();
+        let rows: Vec<_> = args.values_of("pixels").unwrap().collect();
+        assert_eq!(rows, vec!["01110", "0x1F", "4"]);
+
+        let (_, matches) = parse(&["scroll", "moving", "--rate", "fast"]).unwrap();
+        let args = matches.subcommand_matches("scroll").unwrap();
+        assert!(millis(args, "rate", 300).is_err());
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["cursor", "1"]).is_err());
+        assert!(parse(&["backlight", "dim"]).is_err());
+        assert!(parse(&["pipe", "--route", "sideways"]).is_err());
+        assert!(parse(&["info", "--rows", "5"]).is_err());
+    }
+}
@@ -0,0 +1,44 @@
+use std::io;
+
+use clap::{crate_authors, crate_version, App, Arg};
+use gpio_lcd::config;
+use gpio_lcd::daemon::{Daemon, DEFAULT_SOCKET};
+use gpio_lcd::lcd::LcdDriver;
+use gpio_lcd::scheduler::ThreadedLcd;
+use gpio_lcd::sim::Simulator;
+use gpio_lcd::terminal::TerminalBus;
+
+fn main() -> Result<(), String> {
+    let matches = App::new("lcdd")
+        .author(crate_authors!())
+        .version(crate_version!())
+        .about("Shares an LCD between local processes over a Unix socket")
+        .args(&config::args())
+        .arg(
+            Arg::with_name("terminal")
+                .short("t")
+                .long("terminal")
+                .help("Draw a simulated display on this terminal instead of using GPIO")
+                .takes_value(false),
+        )
+        .get_matches();
+
+    let config = config::from_matches(&matches).map_err(|e| format!("{}", e))?;
+    let lcd = if matches.is_present("terminal") {
+        let simulator = Simulator::new(config.cols, config.rows).with_rom(config.rom);
+        LcdDriver::from_bus(
+            TerminalBus::new(simulator, io::stdout()),
+            config.cols,
+            config.rows,
+        )
+        .map(|lcd| lcd.with_rom(config.rom))
+    } else {
+        config.open()
+    }
+    .map_err(|e| format!("{}", e))?;
+
+    let socket = config.socket.as_deref().unwrap_or(DEFAULT_SOCKET);
+    let daemon =
+        Daemon::bind(socket, ThreadedLcd::with_driver(lcd)).map_err(|e| format!("{}", e))?;
+    daemon.run().map_err(|e| format!("{}", e))
+}
@@ -0,0 +1,218 @@
+use crate::lcd::LcdDriver;
+use gpio_cdev::errors::Error;
+
+// A small bitmap made out of up to 8 custom characters. The canvas owns every CGRAM slot, so it
+// can't be used alongside `Icon`s or the other glyph based widgets
+
+const CELL_WIDTH: u8 = 5;
+const CELL_HEIGHT: u8 = 8;
+const MAX_CELLS: u8 = 8;
+
+#[derive(Debug, Clone)]
+pub struct Canvas {
+    cols: u8,
+    rows: u8,
+    cells: Vec<[u8; 8]>,
+    dirty: Vec<bool>,
+}
+
+impl Canvas {
+    // Size is given in cells, e.g. 4x2 cells is a 20x16 pixel canvas
+    pub fn new(cols: u8, rows: u8) -> Self {
+        assert!(
+            cols > 0 && rows > 0 && cols as u16 * rows as u16 <= MAX_CELLS as u16,
+            "A canvas can use at most 8 cells"
+        );
+        let cell_count = (cols * rows) as usize;
+        Canvas {
+            cols,
+            rows,
+            cells: vec![[0; 8]; cell_count],
+            dirty: vec![true; cell_count],
+        }
+    }
+
+    pub fn width(&self) -> u8 {
+        self.cols * CELL_WIDTH
+    }
+
+    pub fn height(&self) -> u8 {
+        self.rows * CELL_HEIGHT
+    }
+
+    pub fn get_pixel(&self, x: u8, y: u8) -> bool {
+        match self.locate(x, y) {
+            Some((cell, row, mask)) => self.cells[cell][row] & mask != 0,
+            None => false,
+        }
+    }
+
+    // Pixels outside the canvas are ignored so shapes can be partially off screen
+    pub fn set_pixel(&mut self, x: u8, y: u8) {
+        if let Some((cell, row, mask)) = self.locate(x, y) {
+            self.cells[cell][row] |= mask;
+            self.dirty[cell] = true;
+        }
+    }
+
+    pub fn clear_pixel(&mut self, x: u8, y: u8) {
+        if let Some((cell, row, mask)) = self.locate(x, y) {
+            self.cells[cell][row] &= !mask;
+            self.dirty[cell] = true;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for (cell, dirty) in self.cells.iter_mut().zip(self.dirty.iter_mut()) {
+            *cell = [0; 8];
+            *dirty = true;
+        }
+    }
+
+    pub fn line(&mut self, x0: u8, y0: u8, x1: u8, y1: u8) {
+        // Bresenham's line algorithm
+        let (mut x, mut y) = (x0 as i16, y0 as i16);
+        let (x1, y1) = (x1 as i16, y1 as i16);
+        let dx = (x1 - x).abs();
+        let dy = -(y1 - y).abs();
+        let step_x = if x < x1 { 1 } else { -1 };
+        let step_y = if y < y1 { 1 } else { -1 };
+        let mut err = dx + dy;
+        loop {
+            self.set_pixel(x as u8, y as u8);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let err2 = 2 * err;
+            if err2 >= dy {
+                err += dy;
+                x += step_x;
+            }
+            if err2 <= dx {
+                err += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    pub fn rect(&mut self, x: u8, y: u8, width: u8, height: u8) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        // Edges past the last pixel a u8 can address are off the canvas anyway
+        let (x1, y1) = (x.saturating_add(width - 1), y.saturating_add(height - 1));
+        self.line(x, y, x1, y);
+        self.line(x, y1, x1, y1);
+        self.line(x, y, x, y1);
+        self.line(x1, y, x1, y1);
+    }
+
+    pub fn fill_rect(&mut self, x: u8, y: u8, width: u8, height: u8) {
+        for px in x..x.saturating_add(width) {
+            for py in y..y.saturating_add(height) {
+                self.set_pixel(px, py);
+            }
+        }
+    }
+
+    // Plot the last `width` values as a connected line, `min` is drawn on the bottom row and
+    // `max` on the top row
+    pub fn plot(&mut self, values: &[f32], min: f32, max: f32) {
+        let shown = &values[values.len().saturating_sub(self.width() as usize)..];
+        let top = (self.height() - 1) as f32;
+        let mut last: Option<(u8, u8)> = None;
+        for (x, value) in shown.iter().enumerate() {
+            let fraction = if max > min {
+                ((value - min) / (max - min)).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let point = (x as u8, (top - (fraction * top).round()) as u8);
+            match last {
+                Some((last_x, last_y)) => self.line(last_x, last_y, point.0, point.1),
+                None => self.set_pixel(point.0, point.1),
+            }
+            last = Some(point);
+        }
+    }
+
+    // Upload any changed cells to CGRAM and lay the cells out with their top left corner at
+    // (row, col)
+    pub fn draw(&mut self, lcd: &LcdDriver, row: u8, col: u8) -> Result<(), Error> {
+        for (loc, cell) in self.cells.iter().enumerate() {
+            if self.dirty[loc] {
+                lcd.create_char(loc as u8, *cell)?;
+                self.dirty[loc] = false;
+            }
+        }
+        for cell_row in 0..self.rows {
+            lcd.set_cursor(row + cell_row, col)?;
+            for cell_col in 0..self.cols {
+                lcd.write(cell_row * self.cols + cell_col)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Cell index, row in the cell and bit mask for a pixel
+    fn locate(&self, x: u8, y: u8) -> Option<(usize, usize, u8)> {
+        if x >= self.width() || y >= self.height() {
+            return None;
+        }
+        let cell = (y / CELL_HEIGHT) * self.cols + x / CELL_WIDTH;
+        let mask = 1 << (CELL_WIDTH - 1 - x % CELL_WIDTH);
+        Some((cell as usize, (y % CELL_HEIGHT) as usize, mask))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::canvas::*;
+
+    #[test]
+    fn pixel_mapping_test() {
+        let mut canvas = Canvas::new(4, 2);
+        assert_eq!((canvas.width(), canvas.height()), (20, 16));
+        canvas.set_pixel(0, 0);
+        canvas.set_pixel(6, 9);
+        canvas.set_pixel(19, 15);
+        canvas.set_pixel(20, 16);
+        assert_eq!(canvas.cells[0][0], 0x10);
+        assert_eq!(canvas.cells[5][1], 0x08);
+        assert_eq!(canvas.cells[7][7], 0x01);
+        assert!(canvas.get_pixel(6, 9));
+        canvas.clear_pixel(6, 9);
+        assert!(!canvas.get_pixel(6, 9));
+    }
+
+    #[test]
+    fn line_test() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.line(0, 0, 9, 0);
+        assert!((0..10).all(|x| canvas.get_pixel(x, 0)));
+        canvas.clear();
+        canvas.line(0, 7, 7, 0);
+        assert!((0..8).all(|i| canvas.get_pixel(i, 7 - i)));
+        assert!(!canvas.get_pixel(0, 0));
+    }
+
+    #[test]
+    fn rect_test() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.rect(0, 0, 0, 5);
+        canvas.rect(3, 3, 5, 0);
+        assert!(canvas.cells.iter().all(|cell| *cell == [0; 8]));
+        canvas.rect(1, 1, 3, 3);
+        assert!(canvas.get_pixel(1, 1) && canvas.get_pixel(3, 3));
+        assert!(!canvas.get_pixel(2, 2));
+        // Running off the edge of the u8 range clips instead of overflowing
+        canvas.rect(8, 6, 255, 255);
+        assert!(canvas.get_pixel(9, 6) && canvas.get_pixel(8, 7));
+    }
+
+    #[test]
+    #[should_panic(expected = "at most 8 cells")]
+    fn too_many_cells_test() {
+        Canvas::new(16, 16);
+    }
+}
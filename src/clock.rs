@@ -0,0 +1,65 @@
+use chrono::{DateTime, Local};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// Source of time for the scheduler, swapped out in tests so job ordering doesn't depend on how
+// fast the test runs
+pub trait Clock: Send + 'static {
+    fn now(&self) -> Instant;
+    // Time of day for jobs that run at set times, read at the same moment as `now`
+    fn local_now(&self) -> DateTime<Local>;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn local_now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+}
+
+// A clock that only moves when told to, clones share the same time
+#[derive(Debug, Clone)]
+pub struct TestClock {
+    now: Arc<Mutex<(Instant, DateTime<Local>)>>,
+}
+
+impl TestClock {
+    pub fn new() -> Self {
+        TestClock::at(Local::now())
+    }
+
+    pub fn at(local: DateTime<Local>) -> Self {
+        TestClock {
+            now: Arc::new(Mutex::new((Instant::now(), local))),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        let mut now = self.now.lock();
+        now.0 += duration;
+        now.1 += chrono::Duration::from_std(duration).unwrap();
+    }
+}
+
+impl Default for TestClock {
+    fn default() -> Self {
+        TestClock::new()
+    }
+}
+
+impl Clock for TestClock {
+    fn now(&self) -> Instant {
+        self.now.lock().0
+    }
+
+    fn local_now(&self) -> DateTime<Local> {
+        self.now.lock().1
+    }
+}
@@ -0,0 +1,318 @@
+use crate::font::Rom;
+use crate::lcd::LcdDriver;
+use gpio_cdev::errors::Error;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::str::FromStr;
+
+// Read by the command line tools when it's there and no other file is given
+pub const DEFAULT_CONFIG: &str = "/etc/gpio_lcd.conf";
+
+// How a display is wired up, for the command line tools. Written as `key = value` lines:
+//
+//     chip = /dev/gpiochip0
+//     cols = 20
+//     rows = 4
+//     four_bit = true
+//     rs = 7
+//     enable = 8
+//     data = 25 24 23 18
+//     backlight = 12
+//
+// `rw` and `backlight` can be left out if they aren't connected, `rom` is `A00` or `A02` and
+// `socket` is where to find the daemon. `#` starts a comment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LcdConfig {
+    pub chip: String,
+    pub cols: u8,
+    pub rows: u8,
+    pub four_bit: bool,
+    pub rs: Option<u8>,
+    pub rw: Option<u8>,
+    pub enable: Option<u8>,
+    // Four lines in four bit mode, otherwise eight
+    pub data: Vec<u8>,
+    pub backlight: Option<u8>,
+    pub rom: Rom,
+    pub socket: Option<String>,
+}
+
+impl Default for LcdConfig {
+    fn default() -> Self {
+        LcdConfig {
+            chip: "/dev/gpiochip0".to_string(),
+            cols: 16,
+            rows: 2,
+            four_bit: false,
+            rs: None,
+            rw: None,
+            enable: None,
+            data: Vec::new(),
+            backlight: None,
+            rom: Rom::A00,
+            socket: None,
+        }
+    }
+}
+
+impl LcdConfig {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let mut config = LcdConfig::default();
+        config.read(&fs::read_to_string(path)?)?;
+        Ok(config)
+    }
+
+    // Sets everything given in `text` on top of what's already there
+    pub fn read(&mut self, text: &str) -> Result<(), Error> {
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut parts = line.splitn(2, '=');
+            match (parts.next(), parts.next()) {
+                (Some(key), Some(value)) => self.set(key.trim(), value.trim())?,
+                _ => {
+                    return Err(invalid(format!(
+                        "Line {} of the config isn't `key = value`: {}",
+                        number + 1,
+                        line
+                    )))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), Error> {
+        match key {
+            "chip" => self.chip = value.to_string(),
+            // The most a single HD44780 drives
+            "cols" => self.cols = within(key, number(key, value)?, 1, 40)?,
+            "rows" => self.rows = within(key, number(key, value)?, 1, 4)?,
+            "four_bit" => {
+                self.four_bit = match value {
+                    "true" | "yes" | "1" => true,
+                    "false" | "no" | "0" => false,
+                    _ => return Err(invalid("four_bit should be true or false".to_string())),
+                }
+            }
+            "rs" => self.rs = Some(number(key, value)?),
+            "rw" => self.rw = Some(number(key, value)?),
+            "enable" => self.enable = Some(number(key, value)?),
+            "data" => {
+                self.data = value
+                    .split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|line| !line.is_empty())
+                    .map(|line| number(key, line))
+                    .collect::<Result<_, _>>()?
+            }
+            "backlight" => self.backlight = Some(number(key, value)?),
+            "rom" => {
+                self.rom = match value {
+                    "A00" | "a00" => Rom::A00,
+                    "A02" | "a02" => Rom::A02,
+                    _ => return Err(invalid("rom should be A00 or A02".to_string())),
+                }
+            }
+            "socket" => self.socket = Some(value.to_string()),
+            _ => return Err(invalid(format!("Unknown config key {}", key))),
+        }
+        Ok(())
+    }
+
+    // Opens the GPIO lines and initialises the display, which clears it
+    pub fn open(&self) -> Result<LcdDriver, Error> {
+        self.driver(true)
+    }
+
+    // Opens the GPIO lines and carries on with what's on the display
+    pub fn attach(&self) -> Result<LcdDriver, Error> {
+        self.driver(false)
+    }
+
+    fn driver(&self, init: bool) -> Result<LcdDriver, Error> {
+        let rs = required(self.rs, "rs")?;
+        let enable = required(self.enable, "enable")?;
+        let wanted = if self.four_bit { 4 } else { 8 };
+        if self.data.len() != wanted {
+            return Err(invalid(format!(
+                "{} data lines are needed, {} were given",
+                wanted,
+                self.data.len()
+            )));
+        }
+        let mut data = [255; 8];
+        data[..wanted].copy_from_slice(&self.data);
+        let connect = if init {
+            LcdDriver::new
+        } else {
+            LcdDriver::attach
+        };
+        let lcd = connect(
+            self.cols,
+            self.rows,
+            &self.chip,
+            self.four_bit,
+            rs,
+            self.rw.unwrap_or(255),
+            enable,
+            data[0],
+            data[1],
+            data[2],
+            data[3],
+            data[4],
+            data[5],
+            data[6],
+            data[7],
+        )?
+        .with_rom(self.rom);
+        match self.backlight {
+            Some(line) => lcd.with_backlight(line),
+            None => Ok(lcd),
+        }
+    }
+}
+
+// The flags every command line tool takes for the config, anything given overrides the file
+#[cfg(feature = "cli")]
+pub fn args<'a, 'b>() -> Vec<clap::Arg<'a, 'b>> {
+    use clap::Arg;
+
+    let value = |name: &'a str, help: &'b str| {
+        Arg::with_name(name)
+            .long(name)
+            .value_name(name)
+            .help(help)
+            .takes_value(true)
+            .global(true)
+    };
+    vec![
+        value(
+            "config",
+            "A config file, /etc/gpio_lcd.conf is read if it's there",
+        )
+        .short("C"),
+        value("chip", "Sets the chip to use for GPIO").short("c"),
+        value("cols", "Columns on the display"),
+        value("rows", "Rows on the display"),
+        Arg::with_name("four_bit_mode")
+            .short("f")
+            .long("mode")
+            .help("Sets the bit mode for the LCD panel")
+            .global(true),
+        value("rs", "The pin to use for rs"),
+        value("rw", "The pin to use for rw"),
+        value("enable", "The pin to use for enable").short("e"),
+        value(
+            "data_pins",
+            "The 8 data pins, or 4 in four bit mode, separated by commas",
+        )
+        .short("d"),
+        value("backlight", "The pin switching the backlight"),
+        value("rom", "The character ROM, A00 or A02"),
+        value("socket", "Where the daemon listens").short("s"),
+    ]
+}
+
+// The config file if there is one with the flags from `args` on top
+#[cfg(feature = "cli")]
+pub fn from_matches(matches: &clap::ArgMatches) -> Result<LcdConfig, Error> {
+    let mut config = match matches.value_of("config") {
+        Some(path) => LcdConfig::load(path)?,
+        None if Path::new(DEFAULT_CONFIG).exists() => LcdConfig::load(DEFAULT_CONFIG)?,
+        None => LcdConfig::default(),
+    };
+    for key in &[
+        "chip",
+        "cols",
+        "rows",
+        "rs",
+        "rw",
+        "enable",
+        "backlight",
+        "rom",
+        "socket",
+    ] {
+        if let Some(value) = matches.value_of(key) {
+            config.set(key, value)?;
+        }
+    }
+    if let Some(data) = matches.value_of("data_pins") {
+        config.set("data", data)?;
+    }
+    if matches.is_present("four_bit_mode") {
+        config.four_bit = true;
+    }
+    Ok(config)
+}
+
+// Also used by the command line tools for their own arguments
+pub fn number<T: FromStr>(key: &str, value: &str) -> Result<T, Error> {
+    T::from_str(value).map_err(|_| invalid(format!("{} should be a number, not {}", key, value)))
+}
+
+fn within(key: &str, value: u8, min: u8, max: u8) -> Result<u8, Error> {
+    if value < min || value > max {
+        return Err(invalid(format!(
+            "{} should be from {} to {}, not {}",
+            key, min, max, value
+        )));
+    }
+    Ok(value)
+}
+
+fn required(line: Option<u8>, name: &str) -> Result<u8, Error> {
+    line.ok_or_else(|| {
+        invalid(format!(
+            "No {} line given, set it in the config file or with --{}",
+            name, name
+        ))
+    })
+}
+
+pub fn invalid(message: String) -> Error {
+    Error::from(std::io::Error::new(ErrorKind::InvalidInput, message))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::config::*;
+
+    #[test]
+    fn config_test() {
+        let mut config = LcdConfig::default();
+        config
+            .read(
+                "# The display on the front panel\n\
+                 cols = 20\n\
+                 rows=4\n\
+                 four_bit = yes\n\
+                 rs = 7 # RS\n\
+                 enable = 8\n\
+                 data = 25, 24 23 18\n\
+                 rom = A02\n",
+            )
+            .unwrap();
+        assert_eq!((config.cols, config.rows), (20, 4));
+        assert!(config.four_bit);
+        assert_eq!(
+            (config.rs, config.rw, config.enable),
+            (Some(7), None, Some(8))
+        );
+        assert_eq!(config.data, vec![25, 24, 23, 18]);
+        assert_eq!(config.rom, Rom::A02);
+        assert!(config.read("cols = many").is_err());
+        assert!(config.read("cols = 0").is_err());
+        assert!(config.read("cols = 41").is_err());
+        assert!(config.read("rows = 5").is_err());
+        assert_eq!((config.cols, config.rows), (20, 4));
+        assert!(config.read("colour = green").is_err());
+        assert!(config.read("rs 7").is_err());
+
+        // Lines that are missing are caught before any GPIO is touched
+        let config = LcdConfig { rs: None, ..config };
+        assert!(config.attach().unwrap_err().to_string().contains("rs"));
+    }
+}
@@ -0,0 +1,548 @@
+use crate::notify::{Notification, NotificationId};
+use crate::scheduler::{Job, JobHandle, JobId, ThreadedLcd};
+use gpio_cdev::errors::Error;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+// Where the daemon listens unless it's told otherwise
+pub const DEFAULT_SOCKET: &str = "/run/gpio_lcd.sock";
+
+// Owns the display and lets any number of local processes share it over a Unix socket. Each
+// connection sends requests as lines of JSON and gets a line back for each. Jobs belong to the
+// connection that added them, when it goes away they're cancelled and their rows blanked. Text
+// set on a row belongs to the daemon and stays after the client has gone
+pub struct Daemon {
+    listener: UnixListener,
+    lcd: Arc<ThreadedLcd>,
+    rows: Rows,
+    path: PathBuf,
+}
+
+// The job showing the text set on each row
+type Rows = Arc<Mutex<HashMap<u8, JobHandle>>>;
+
+// How often text set on a row is redrawn, so it comes back after a notification and scrolls when
+// it's too long
+const ROW_RATE: Duration = Duration::from_millis(500);
+
+// Something for the daemon to do, like `{"cmd":"set_row","row":0,"text":"Hello"}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum Request {
+    // Replaces whatever text was set on the row and this client's jobs there
+    SetRow {
+        row: u8,
+        text: String,
+    },
+    AddJob {
+        row: u8,
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        rate_ms: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        times: Option<u32>,
+    },
+    CancelJob {
+        id: JobId,
+    },
+    Notify {
+        text: String,
+        duration_ms: u64,
+        #[serde(default)]
+        priority: u8,
+    },
+    Dismiss {
+        id: NotificationId,
+    },
+    Backlight {
+        on: bool,
+    },
+    // Everyone's jobs go, not just this client's
+    Clear,
+    // The size of the display
+    Info,
+}
+
+// The reply to each request, `id` is the job or notification it added
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Response {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cols: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rows: Option<u8>,
+}
+
+// Talks to a running daemon
+pub struct Client {
+    stream: BufReader<UnixStream>,
+}
+
+// What one connection has added
+struct Session {
+    lcd: Arc<ThreadedLcd>,
+    rows: Rows,
+    jobs: Vec<(u8, JobHandle)>,
+    notifications: Vec<NotificationId>,
+}
+
+impl Daemon {
+    // A socket left behind by a daemon that's no longer running is replaced, one that's still
+    // answering is left alone
+    pub fn bind<P: AsRef<Path>>(path: P, lcd: ThreadedLcd) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if path.exists() {
+            if UnixStream::connect(&path).is_ok() {
+                return Err(io::Error::new(
+                    ErrorKind::AddrInUse,
+                    format!("A daemon is already listening on {}", path.display()),
+                ));
+            }
+            fs::remove_file(&path)?;
+        }
+        Ok(Daemon {
+            listener: UnixListener::bind(&path)?,
+            lcd: Arc::new(lcd),
+            rows: Arc::new(Mutex::new(HashMap::new())),
+            path,
+        })
+    }
+
+    pub fn lcd(&self) -> &ThreadedLcd {
+        &self.lcd
+    }
+
+    // Serves clients until accepting a connection fails, each on its own thread
+    pub fn run(&self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            let mut session = Session::new(Arc::clone(&self.lcd), Arc::clone(&self.rows));
+            thread::spawn(move || {
+                // Whatever went wrong, the client is gone and its jobs go with it
+                let _ = session.serve(stream);
+                session.close();
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+impl Session {
+    fn new(lcd: Arc<ThreadedLcd>, rows: Rows) -> Self {
+        Session {
+            lcd,
+            rows,
+            jobs: Vec::new(),
+            notifications: Vec::new(),
+        }
+    }
+
+    fn serve(&mut self, stream: UnixStream) -> io::Result<()> {
+        let mut out = stream.try_clone()?;
+        for line in BufReader::new(stream).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let result = match serde_json::from_str(&line) {
+                Ok(request) => self.handle(request),
+                Err(error) => Err(format!("Bad request: {}", error)),
+            };
+            let response = result.unwrap_or_else(|error| Response {
+                ok: false,
+                error: Some(error),
+                ..Response::done()
+            });
+            let mut reply = serde_json::to_string(&response)?;
+            reply.push('\n');
+            out.write_all(reply.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn handle(&mut self, request: Request) -> Result<Response, String> {
+        // Jobs that ran out of runs don't need cancelling later
+        self.jobs.retain(|(_, handle)| handle.is_active());
+        match request {
+            Request::SetRow { row, text } => {
+                self.check_row(row)?;
+                for (_, handle) in self.jobs.iter().filter(|(on, _)| *on == row) {
+                    handle.cancel();
+                }
+                self.jobs.retain(|(on, _)| *on != row);
+                let mut rows = self.rows.lock();
+                if let Some(previous) = rows.remove(&row) {
+                    previous.cancel();
+                }
+                let handle = self.lcd.add_job(Job::new(&text, row, Some(ROW_RATE)));
+                let id = handle.id();
+                rows.insert(row, handle);
+                Ok(Response::added(id))
+            }
+            Request::AddJob {
+                row,
+                text,
+                rate_ms,
+                times,
+            } => {
+                self.check_row(row)?;
+                if rate_ms == Some(0) {
+                    return Err("A job's rate has to be at least 1ms".to_string());
+                }
+                let mut job = Job::new(&text, row, rate_ms.map(Duration::from_millis));
+                if let Some(times) = times {
+                    job = job.times(times);
+                }
+                Ok(Response::added(self.add(row, job)))
+            }
+            Request::CancelJob { id } => {
+                match self.jobs.iter().position(|(_, handle)| handle.id() == id) {
+                    Some(index) => {
+                        self.jobs.remove(index).1.cancel();
+                        Ok(Response::done())
+                    }
+                    None => Err(format!("No job {} belonging to this client", id)),
+                }
+            }
+            Request::Notify {
+                text,
+                duration_ms,
+                priority,
+            } => {
+                let notification = Notification::new(&text, Duration::from_millis(duration_ms))
+                    .with_priority(priority);
+                let id = self.lcd.notify(notification);
+                self.notifications.push(id);
+                Ok(Response::added(id))
+            }
+            Request::Dismiss { id } => {
+                if !self.notifications.contains(&id) {
+                    return Err(format!("No notification {} belonging to this client", id));
+                }
+                self.notifications.retain(|&sent| sent != id);
+                self.lcd.dismiss(id);
+                Ok(Response::done())
+            }
+            Request::Backlight { on } => self
+                .lcd
+                .backlight(on)
+                .map(|_| Response::done())
+                .map_err(message),
+            Request::Clear => {
+                self.jobs.clear();
+                self.rows.lock().clear();
+                self.lcd.clear().map(|_| Response::done()).map_err(message)
+            }
+            Request::Info => Ok(Response {
+                cols: Some(self.lcd.get_cols()),
+                rows: Some(self.lcd.get_rows()),
+                ..Response::done()
+            }),
+        }
+    }
+
+    fn check_row(&self, row: u8) -> Result<(), String> {
+        let rows = self.lcd.get_rows();
+        if row >= rows {
+            return Err(format!(
+                "Row {} is off the display, which has {} rows",
+                row, rows
+            ));
+        }
+        Ok(())
+    }
+
+    fn add(&mut self, row: u8, job: Job) -> JobId {
+        let handle = self.lcd.add_job(job);
+        let id = handle.id();
+        self.jobs.push((row, handle));
+        id
+    }
+
+    fn close(&mut self) {
+        let mut rows = BTreeSet::new();
+        for (row, handle) in self.jobs.drain(..) {
+            if handle.cancel() {
+                rows.insert(row);
+            }
+        }
+        // Notifications run out by themselves, so one sent just before leaving still shows. Other
+        // clients' jobs and set rows come back where the cancelled jobs were
+        for row in rows {
+            let _ = self.lcd.redraw_row(row);
+        }
+    }
+}
+
+impl Response {
+    pub fn done() -> Self {
+        Response {
+            ok: true,
+            id: None,
+            error: None,
+            cols: None,
+            rows: None,
+        }
+    }
+
+    pub fn added(id: u64) -> Self {
+        Response {
+            id: Some(id),
+            ..Response::done()
+        }
+    }
+}
+
+impl Client {
+    pub fn connect<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Ok(Client {
+            stream: BufReader::new(UnixStream::connect(path)?),
+        })
+    }
+
+    // Sends any request and waits for the daemon's reply
+    pub fn request(&mut self, request: &Request) -> Result<Response, Error> {
+        let mut line = serde_json::to_string(request).map_err(io::Error::from)?;
+        line.push('\n');
+        self.stream.get_mut().write_all(line.as_bytes())?;
+        let mut reply = String::new();
+        if self.stream.read_line(&mut reply)? == 0 {
+            return Err(Error::from(io::Error::new(
+                ErrorKind::UnexpectedEof,
+                "The daemon closed the connection",
+            )));
+        }
+        Ok(serde_json::from_str(&reply).map_err(io::Error::from)?)
+    }
+
+    pub fn set_row(&mut self, row: u8, text: &str) -> Result<JobId, Error> {
+        self.call_for_id(Request::SetRow {
+            row,
+            text: text.to_string(),
+        })
+    }
+
+    // Rates go over in whole milliseconds, so anything shorter than one is turned down
+    pub fn add_job(&mut self, row: u8, text: &str, rate: Option<Duration>) -> Result<JobId, Error> {
+        if rate.is_some_and(|rate| rate < Duration::from_millis(1)) {
+            return Err(Error::from(io::Error::new(
+                ErrorKind::InvalidInput,
+                "A job's rate has to be at least 1ms",
+            )));
+        }
+        self.call_for_id(Request::AddJob {
+            row,
+            text: text.to_string(),
+            rate_ms: rate.map(|rate| rate.as_millis() as u64),
+            times: None,
+        })
+    }
+
+    pub fn cancel_job(&mut self, id: JobId) -> Result<(), Error> {
+        self.call(Request::CancelJob { id }).map(|_| ())
+    }
+
+    pub fn notify(
+        &mut self,
+        text: &str,
+        duration: Duration,
+        priority: u8,
+    ) -> Result<NotificationId, Error> {
+        self.call_for_id(Request::Notify {
+            text: text.to_string(),
+            duration_ms: duration.as_millis() as u64,
+            priority,
+        })
+    }
+
+    pub fn dismiss(&mut self, id: NotificationId) -> Result<(), Error> {
+        self.call(Request::Dismiss { id }).map(|_| ())
+    }
+
+    pub fn backlight(&mut self, on: bool) -> Result<(), Error> {
+        self.call(Request::Backlight { on }).map(|_| ())
+    }
+
+    pub fn clear(&mut self) -> Result<(), Error> {
+        self.call(Request::Clear).map(|_| ())
+    }
+
+    // Columns and rows
+    pub fn size(&mut self) -> Result<(u8, u8), Error> {
+        let response = self.call(Request::Info)?;
+        match (response.cols, response.rows) {
+            (Some(cols), Some(rows)) => Ok((cols, rows)),
+            _ => Err(Error::from(io::Error::new(
+                ErrorKind::InvalidData,
+                "The daemon didn't say how big the display is",
+            ))),
+        }
+    }
+
+    // A request the daemon turned down comes back as an error with its reason
+    fn call(&mut self, request: Request) -> Result<Response, Error> {
+        let response = self.request(&request)?;
+        if response.ok {
+            Ok(response)
+        } else {
+            Err(Error::from(io::Error::other(
+                response
+                    .error
+                    .unwrap_or_else(|| "The daemon turned the request down".to_string()),
+            )))
+        }
+    }
+
+    fn call_for_id(&mut self, request: Request) -> Result<u64, Error> {
+        self.call(request)?.id.ok_or_else(|| {
+            Error::from(io::Error::new(
+                ErrorKind::InvalidData,
+                "The daemon didn't say what was added",
+            ))
+        })
+    }
+}
+
+fn message(error: Error) -> String {
+    error.to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::daemon::*;
+    use crate::lcd::LcdDriver;
+    use crate::sim::Simulator;
+    use std::thread::sleep;
+    use std::time::Instant;
+
+    // Removes the socket however the test ends, the daemon's own cleanup never runs since it's
+    // left serving on its thread
+    struct Socket(PathBuf);
+
+    impl Drop for Socket {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn request_test() {
+        let request: Request =
+            serde_json::from_str(r#"{"cmd":"add_job","row":1,"text":"hi","rate_ms":500}"#).unwrap();
+        assert_eq!(
+            request,
+            Request::AddJob {
+                row: 1,
+                text: "hi".to_string(),
+                rate_ms: Some(500),
+                times: None,
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&Request::Clear).unwrap(),
+            r#"{"cmd":"clear"}"#
+        );
+        let response: Response = serde_json::from_str(r#"{"ok":true,"id":3}"#).unwrap();
+        assert_eq!(response.id, Some(3));
+    }
+
+    #[test]
+    fn daemon_test() {
+        let socket =
+            Socket(std::env::temp_dir().join(format!("gpio_lcd_test_{}.sock", std::process::id())));
+        let path = &socket.0;
+        let simulator = Simulator::new(16, 2);
+        let lcd = ThreadedLcd::with_driver(LcdDriver::from_bus(simulator.clone(), 16, 2).unwrap());
+        let daemon = Daemon::bind(path, lcd).unwrap();
+        thread::spawn(move || daemon.run());
+        // Waits for the display to show what's expected, the daemon gets to requests in its own time
+        let wait_for = |shown: &dyn Fn(&[String]) -> bool| {
+            let deadline = Instant::now() + Duration::from_secs(5);
+            loop {
+                let lines = simulator.controller().lines('?');
+                if shown(&lines) {
+                    return;
+                }
+                assert!(Instant::now() < deadline, "the display shows {:?}", lines);
+                sleep(Duration::from_millis(1));
+            }
+        };
+        let lines_are = |expected: [&str; 2]| wait_for(&|lines| lines == expected);
+
+        let mut first = Client::connect(path).unwrap();
+        let mut second = Client::connect(path).unwrap();
+        assert_eq!(second.size().unwrap(), (16, 2));
+        first.set_row(0, "first").unwrap();
+        let job = first
+            .add_job(1, "ticking", Some(Duration::from_millis(10)))
+            .unwrap();
+        lines_are(["first           ", "ticking         "]);
+
+        // Only the client that added a job can cancel it
+        assert!(second.cancel_job(job).is_err());
+        second.set_row(0, "second").unwrap();
+        lines_are(["second          ", "ticking         "]);
+
+        // Rows off the display and rates too short to send are turned down
+        assert!(second.set_row(2, "nowhere").is_err());
+        assert!(second.add_job(2, "nowhere", None).is_err());
+        assert!(second
+            .add_job(0, "fast", Some(Duration::from_micros(500)))
+            .is_err());
+        let response = second
+            .request(&Request::AddJob {
+                row: 0,
+                text: "fast".to_string(),
+                rate_ms: Some(0),
+                times: None,
+            })
+            .unwrap();
+        assert!(!response.ok);
+
+        let mut raw = second.stream.get_ref().try_clone().unwrap();
+        raw.write_all(b"{\"cmd\":\"reboot\"}\n").unwrap();
+        let mut reply = String::new();
+        second.stream.read_line(&mut reply).unwrap();
+        assert!(reply.starts_with(r#"{"ok":false,"error":"Bad request"#));
+
+        // Going away takes the ticking job with it
+        drop(first);
+        lines_are(["second          ", "                "]);
+        second.set_row(1, "still here").unwrap();
+        lines_are(["second          ", "still here      "]);
+        // Set text outlasts a notification
+        second.notify("ping", Duration::from_millis(20), 0).unwrap();
+        wait_for(&|lines| lines.iter().any(|line| line.contains("ping")));
+        lines_are(["second          ", "still here      "]);
+
+        // A client leaving doesn't blank text set by another one
+        let mut third = Client::connect(path).unwrap();
+        third.add_job(1, "visiting", None).unwrap();
+        lines_are(["second          ", "visiting        "]);
+        drop(third);
+        lines_are(["second          ", "still here      "]);
+        assert!(Daemon::bind(
+            path,
+            ThreadedLcd::with_driver(LcdDriver::from_bus(Simulator::new(16, 2), 16, 2).unwrap())
+        )
+        .is_err());
+    }
+}
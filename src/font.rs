@@ -0,0 +1,589 @@
+// Glyphs from the controller's character ROM, for backends that draw the display themselves.
+// Each glyph is eight rows of five pixels in the low bits, the same layout as CGRAM and
+// `Icon::char_data`. The last row is left for the cursor, apart from the few letters with
+// descenders which are cut off there like on the real panel
+
+use crate::hd44780;
+
+// Which character ROM the controller was made with, it's the suffix on the part number
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rom {
+    // The standard ROM, ASCII (with a yen sign for `\` and arrows for `~` and DEL), katakana and
+    // some Greek letters and maths symbols
+    #[default]
+    A00,
+    // The European ROM, all of ASCII, Latin-1, some Cyrillic and Greek letters and symbols
+    A02,
+}
+
+impl Rom {
+    // `None` for 0 to 15, which come from CGRAM. Codes the ROM leaves empty are blank glyphs
+    pub fn glyph(&self, code: u8) -> Option<[u8; 8]> {
+        let table = match self {
+            Rom::A00 => &A00,
+            Rom::A02 => &A02,
+        };
+        match code {
+            0x00..=0x0F => None,
+            _ => Some(table[(code - 0x10) as usize]),
+        }
+    }
+
+    // The character code for `c`, for the A00 ROM this is `hd44780::encode`
+    pub fn encode(&self, c: char) -> Option<u8> {
+        match self {
+            Rom::A00 => hd44780::encode(c),
+            Rom::A02 if c.is_ascii() => Some(c as u8),
+            Rom::A02 if c == '⌂' => Some(0x7F),
+            Rom::A02 => match A02_SYMBOLS.iter().position(|&symbol| symbol == c) {
+                Some(index) => Some(index as u8 + 0x10),
+                None => A02_CHARS
+                    .iter()
+                    .position(|&rom_char| rom_char == c)
+                    .map(|index| index as u8 + 0x80),
+            },
+        }
+    }
+
+    // The character a code shows, `None` for CGRAM and anything without an obvious character
+    pub fn decode(&self, code: u8) -> Option<char> {
+        match (self, code) {
+            (Rom::A00, _) => hd44780::decode(code),
+            (Rom::A02, 0x10..=0x1F) => Some(A02_SYMBOLS[(code - 0x10) as usize]),
+            (Rom::A02, 0x20..=0x7E) => Some(code as char),
+            (Rom::A02, 0x7F) => Some('⌂'),
+            (Rom::A02, 0x80..=0xFF) => Some(A02_CHARS[(code - 0x80) as usize]),
+            _ => None,
+        }
+    }
+}
+
+// 0x10 to 0x1F of the A02 ROM
+const A02_SYMBOLS: [char; 16] = [
+    '▶', '◀', '“', '”', '⏫', '⏬', '●', '↵', '↑', '↓', '→', '←', '≤', '≥', '▲', '▼',
+];
+
+// 0x80 to 0xFF of the A02 ROM, mostly Latin-1 from 0xA0
+const A02_CHARS: [char; 128] = [
+    'Б', 'Д', 'Ж', 'З', 'И', 'Й', 'Л', 'П', 'У', 'Ц', 'Ч', 'Ш', 'Щ', 'Ъ', 'Ы', 'Э', //
+    'α', '♪', 'Γ', 'π', 'Σ', 'σ', '♬', 'τ', '🔔', 'Θ', 'Ω', 'δ', '∞', '♥', 'ε', '∩', //
+    '\u{a0}', '¡', '¢', '£', '¤', '¥', '¦', '§', 'ƒ', '©', 'ª', '«', 'Ю', 'Я', '®', '‘', //
+    '°', '±', '²', '³', '₧', 'µ', '¶', '·', 'ω', '¹', 'º', '»', '¼', '½', '¾', '¿', //
+    'À', 'Á', 'Â', 'Ã', 'Ä', 'Å', 'Æ', 'Ç', 'È', 'É', 'Ê', 'Ë', 'Ì', 'Í', 'Î', 'Ï', //
+    'Ð', 'Ñ', 'Ò', 'Ó', 'Ô', 'Õ', 'Ö', '×', 'Ø', 'Ù', 'Ú', 'Û', 'Ü', 'Ý', 'Þ', 'ß', //
+    'à', 'á', 'â', 'ã', 'ä', 'å', 'æ', 'ç', 'è', 'é', 'ê', 'ë', 'ì', 'í', 'î', 'ï', //
+    'ð', 'ñ', 'ò', 'ó', 'ô', 'õ', 'ö', '÷', 'ø', 'ù', 'ú', 'û', 'ü', 'ý', 'þ', 'ÿ', //
+];
+
+// Both tables start at 0x10, after the CGRAM codes
+const A00: [[u8; 8]; 240] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x10
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x11
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x12
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x13
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x14
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x15
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x16
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x17
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x18
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x19
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x1A
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x1B
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x1C
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x1D
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x1E
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x1F
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x20
+    [0x04, 0x04, 0x04, 0x04, 0x00, 0x00, 0x04, 0x00], // 0x21
+    [0x0A, 0x0A, 0x0A, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x22
+    [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A, 0x00], // 0x23
+    [0x04, 0x0F, 0x14, 0x0E, 0x05, 0x1E, 0x04, 0x00], // 0x24
+    [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03, 0x00], // 0x25
+    [0x0C, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0D, 0x00], // 0x26
+    [0x0C, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x27
+    [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02, 0x00], // 0x28
+    [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08, 0x00], // 0x29
+    [0x00, 0x04, 0x15, 0x0E, 0x15, 0x04, 0x00, 0x00], // 0x2A
+    [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00, 0x00], // 0x2B
+    [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08, 0x00], // 0x2C
+    [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00, 0x00], // 0x2D
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x00], // 0x2E
+    [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00, 0x00], // 0x2F
+    [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E, 0x00], // 0x30
+    [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E, 0x00], // 0x31
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F, 0x00], // 0x32
+    [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E, 0x00], // 0x33
+    [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02, 0x00], // 0x34
+    [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E, 0x00], // 0x35
+    [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E, 0x00], // 0x36
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08, 0x00], // 0x37
+    [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E, 0x00], // 0x38
+    [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C, 0x00], // 0x39
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00, 0x00], // 0x3A
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x04, 0x08, 0x00], // 0x3B
+    [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02, 0x00], // 0x3C
+    [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00, 0x00], // 0x3D
+    [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08, 0x00], // 0x3E
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04, 0x00], // 0x3F
+    [0x0E, 0x11, 0x01, 0x0D, 0x15, 0x15, 0x0E, 0x00], // 0x40
+    [0x0E, 0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x00], // 0x41
+    [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E, 0x00], // 0x42
+    [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E, 0x00], // 0x43
+    [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C, 0x00], // 0x44
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F, 0x00], // 0x45
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10, 0x00], // 0x46
+    [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F, 0x00], // 0x47
+    [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11, 0x00], // 0x48
+    [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E, 0x00], // 0x49
+    [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C, 0x00], // 0x4A
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11, 0x00], // 0x4B
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F, 0x00], // 0x4C
+    [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11, 0x00], // 0x4D
+    [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11, 0x00], // 0x4E
+    [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E, 0x00], // 0x4F
+    [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10, 0x00], // 0x50
+    [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D, 0x00], // 0x51
+    [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11, 0x00], // 0x52
+    [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E, 0x00], // 0x53
+    [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x00], // 0x54
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E, 0x00], // 0x55
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04, 0x00], // 0x56
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A, 0x00], // 0x57
+    [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11, 0x00], // 0x58
+    [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04, 0x00], // 0x59
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F, 0x00], // 0x5A
+    [0x0E, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0E, 0x00], // 0x5B
+    [0x11, 0x0A, 0x1F, 0x04, 0x1F, 0x04, 0x04, 0x00], // 0x5C
+    [0x0E, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0E, 0x00], // 0x5D
+    [0x04, 0x0A, 0x11, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x5E
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F, 0x00], // 0x5F
+    [0x08, 0x04, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x60
+    [0x00, 0x00, 0x0E, 0x01, 0x0F, 0x11, 0x0F, 0x00], // 0x61
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x1E, 0x00], // 0x62
+    [0x00, 0x00, 0x0E, 0x10, 0x10, 0x11, 0x0E, 0x00], // 0x63
+    [0x01, 0x01, 0x0D, 0x13, 0x11, 0x11, 0x0F, 0x00], // 0x64
+    [0x00, 0x00, 0x0E, 0x11, 0x1F, 0x10, 0x0E, 0x00], // 0x65
+    [0x06, 0x09, 0x08, 0x1C, 0x08, 0x08, 0x08, 0x00], // 0x66
+    [0x00, 0x0F, 0x11, 0x11, 0x0F, 0x01, 0x0E, 0x00], // 0x67
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x11, 0x00], // 0x68
+    [0x04, 0x00, 0x0C, 0x04, 0x04, 0x04, 0x0E, 0x00], // 0x69
+    [0x02, 0x00, 0x06, 0x02, 0x02, 0x12, 0x0C, 0x00], // 0x6A
+    [0x10, 0x10, 0x12, 0x14, 0x18, 0x14, 0x12, 0x00], // 0x6B
+    [0x0C, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E, 0x00], // 0x6C
+    [0x00, 0x00, 0x1A, 0x15, 0x15, 0x11, 0x11, 0x00], // 0x6D
+    [0x00, 0x00, 0x16, 0x19, 0x11, 0x11, 0x11, 0x00], // 0x6E
+    [0x00, 0x00, 0x0E, 0x11, 0x11, 0x11, 0x0E, 0x00], // 0x6F
+    [0x00, 0x00, 0x1E, 0x11, 0x1E, 0x10, 0x10, 0x00], // 0x70
+    [0x00, 0x00, 0x0D, 0x13, 0x0F, 0x01, 0x01, 0x00], // 0x71
+    [0x00, 0x00, 0x16, 0x19, 0x10, 0x10, 0x10, 0x00], // 0x72
+    [0x00, 0x00, 0x0E, 0x10, 0x0E, 0x01, 0x1E, 0x00], // 0x73
+    [0x08, 0x08, 0x1C, 0x08, 0x08, 0x09, 0x06, 0x00], // 0x74
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x13, 0x0D, 0x00], // 0x75
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x0A, 0x04, 0x00], // 0x76
+    [0x00, 0x00, 0x11, 0x11, 0x15, 0x15, 0x0A, 0x00], // 0x77
+    [0x00, 0x00, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x00], // 0x78
+    [0x00, 0x00, 0x11, 0x11, 0x0F, 0x01, 0x0E, 0x00], // 0x79
+    [0x00, 0x00, 0x1F, 0x02, 0x04, 0x08, 0x1F, 0x00], // 0x7A
+    [0x02, 0x04, 0x04, 0x08, 0x04, 0x04, 0x02, 0x00], // 0x7B
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x00], // 0x7C
+    [0x08, 0x04, 0x04, 0x02, 0x04, 0x04, 0x08, 0x00], // 0x7D
+    [0x00, 0x04, 0x02, 0x1F, 0x02, 0x04, 0x00, 0x00], // 0x7E
+    [0x00, 0x04, 0x08, 0x1F, 0x08, 0x04, 0x00, 0x00], // 0x7F
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x80
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x81
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x82
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x83
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x84
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x85
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x86
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x87
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x88
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x89
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x8A
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x8B
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x8C
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x8D
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x8E
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x8F
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x90
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x91
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x92
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x93
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x94
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x95
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x96
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x97
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x98
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x99
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x9A
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x9B
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x9C
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x9D
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x9E
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x9F
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0xA0
+    [0x00, 0x00, 0x00, 0x00, 0x1C, 0x14, 0x1C, 0x00], // 0xA1
+    [0x07, 0x04, 0x04, 0x04, 0x00, 0x00, 0x00, 0x00], // 0xA2
+    [0x00, 0x00, 0x00, 0x04, 0x04, 0x04, 0x1C, 0x00], // 0xA3
+    [0x00, 0x00, 0x00, 0x00, 0x10, 0x08, 0x04, 0x00], // 0xA4
+    [0x00, 0x00, 0x00, 0x0C, 0x0C, 0x00, 0x00, 0x00], // 0xA5
+    [0x00, 0x1F, 0x01, 0x1F, 0x01, 0x02, 0x0C, 0x00], // 0xA6
+    [0x00, 0x00, 0x1F, 0x01, 0x06, 0x04, 0x08, 0x00], // 0xA7
+    [0x00, 0x00, 0x02, 0x04, 0x0C, 0x14, 0x04, 0x00], // 0xA8
+    [0x00, 0x00, 0x04, 0x1F, 0x11, 0x01, 0x06, 0x00], // 0xA9
+    [0x00, 0x00, 0x00, 0x1F, 0x04, 0x04, 0x1F, 0x00], // 0xAA
+    [0x00, 0x00, 0x02, 0x1F, 0x06, 0x0A, 0x12, 0x00], // 0xAB
+    [0x00, 0x00, 0x08, 0x1F, 0x09, 0x0A, 0x08, 0x00], // 0xAC
+    [0x00, 0x00, 0x00, 0x0E, 0x02, 0x02, 0x1F, 0x00], // 0xAD
+    [0x00, 0x00, 0x1E, 0x02, 0x1E, 0x02, 0x1E, 0x00], // 0xAE
+    [0x00, 0x00, 0x00, 0x15, 0x15, 0x01, 0x06, 0x00], // 0xAF
+    [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00, 0x00], // 0xB0
+    [0x1F, 0x01, 0x05, 0x06, 0x04, 0x04, 0x08, 0x00], // 0xB1
+    [0x01, 0x02, 0x04, 0x0C, 0x14, 0x04, 0x04, 0x00], // 0xB2
+    [0x04, 0x1F, 0x11, 0x11, 0x01, 0x02, 0x04, 0x00], // 0xB3
+    [0x00, 0x1F, 0x04, 0x04, 0x04, 0x04, 0x1F, 0x00], // 0xB4
+    [0x02, 0x1F, 0x02, 0x06, 0x0A, 0x12, 0x02, 0x00], // 0xB5
+    [0x08, 0x1F, 0x09, 0x09, 0x09, 0x09, 0x12, 0x00], // 0xB6
+    [0x04, 0x1F, 0x04, 0x1F, 0x04, 0x04, 0x04, 0x00], // 0xB7
+    [0x00, 0x0F, 0x09, 0x11, 0x01, 0x02, 0x0C, 0x00], // 0xB8
+    [0x08, 0x0F, 0x12, 0x02, 0x02, 0x02, 0x04, 0x00], // 0xB9
+    [0x00, 0x1F, 0x01, 0x01, 0x01, 0x01, 0x1F, 0x00], // 0xBA
+    [0x0A, 0x1F, 0x0A, 0x0A, 0x02, 0x04, 0x08, 0x00], // 0xBB
+    [0x00, 0x18, 0x01, 0x19, 0x01, 0x02, 0x1C, 0x00], // 0xBC
+    [0x00, 0x1F, 0x01, 0x02, 0x04, 0x0A, 0x11, 0x00], // 0xBD
+    [0x08, 0x1F, 0x09, 0x0A, 0x08, 0x08, 0x07, 0x00], // 0xBE
+    [0x00, 0x11, 0x11, 0x09, 0x01, 0x02, 0x0C, 0x00], // 0xBF
+    [0x00, 0x0F, 0x09, 0x17, 0x01, 0x02, 0x0C, 0x00], // 0xC0
+    [0x02, 0x1C, 0x04, 0x1F, 0x04, 0x04, 0x08, 0x00], // 0xC1
+    [0x00, 0x15, 0x15, 0x15, 0x01, 0x02, 0x04, 0x00], // 0xC2
+    [0x0E, 0x00, 0x1F, 0x04, 0x04, 0x04, 0x08, 0x00], // 0xC3
+    [0x08, 0x08, 0x08, 0x0C, 0x0A, 0x08, 0x08, 0x00], // 0xC4
+    [0x04, 0x04, 0x1F, 0x04, 0x04, 0x08, 0x10, 0x00], // 0xC5
+    [0x00, 0x0E, 0x00, 0x00, 0x00, 0x00, 0x1F, 0x00], // 0xC6
+    [0x00, 0x1F, 0x01, 0x0A, 0x04, 0x0A, 0x10, 0x00], // 0xC7
+    [0x04, 0x1F, 0x02, 0x04, 0x0E, 0x15, 0x04, 0x00], // 0xC8
+    [0x02, 0x02, 0x02, 0x02, 0x02, 0x04, 0x08, 0x00], // 0xC9
+    [0x00, 0x04, 0x02, 0x11, 0x11, 0x11, 0x11, 0x00], // 0xCA
+    [0x10, 0x10, 0x1F, 0x10, 0x10, 0x10, 0x0F, 0x00], // 0xCB
+    [0x00, 0x1F, 0x01, 0x01, 0x01, 0x02, 0x0C, 0x00], // 0xCC
+    [0x00, 0x08, 0x14, 0x02, 0x01, 0x01, 0x00, 0x00], // 0xCD
+    [0x04, 0x1F, 0x04, 0x04, 0x15, 0x15, 0x04, 0x00], // 0xCE
+    [0x00, 0x1F, 0x01, 0x01, 0x0A, 0x04, 0x02, 0x00], // 0xCF
+    [0x00, 0x0E, 0x00, 0x0E, 0x00, 0x0E, 0x01, 0x00], // 0xD0
+    [0x00, 0x04, 0x08, 0x10, 0x11, 0x1F, 0x01, 0x00], // 0xD1
+    [0x00, 0x01, 0x01, 0x0A, 0x04, 0x0A, 0x10, 0x00], // 0xD2
+    [0x00, 0x1F, 0x08, 0x1F, 0x08, 0x08, 0x07, 0x00], // 0xD3
+    [0x08, 0x08, 0x1F, 0x09, 0x0A, 0x08, 0x08, 0x00], // 0xD4
+    [0x00, 0x0E, 0x02, 0x02, 0x02, 0x02, 0x1F, 0x00], // 0xD5
+    [0x00, 0x1F, 0x01, 0x1F, 0x01, 0x01, 0x1F, 0x00], // 0xD6
+    [0x0E, 0x00, 0x1F, 0x01, 0x01, 0x02, 0x04, 0x00], // 0xD7
+    [0x12, 0x12, 0x12, 0x12, 0x02, 0x04, 0x08, 0x00], // 0xD8
+    [0x00, 0x04, 0x14, 0x14, 0x15, 0x15, 0x16, 0x00], // 0xD9
+    [0x00, 0x10, 0x10, 0x11, 0x12, 0x14, 0x18, 0x00], // 0xDA
+    [0x00, 0x1F, 0x11, 0x11, 0x11, 0x11, 0x1F, 0x00], // 0xDB
+    [0x00, 0x1F, 0x11, 0x11, 0x01, 0x02, 0x04, 0x00], // 0xDC
+    [0x00, 0x18, 0x01, 0x01, 0x02, 0x04, 0x18, 0x00], // 0xDD
+    [0x04, 0x12, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00], // 0xDE
+    [0x1C, 0x14, 0x1C, 0x00, 0x00, 0x00, 0x00, 0x00], // 0xDF
+    [0x00, 0x00, 0x09, 0x15, 0x12, 0x12, 0x0D, 0x00], // 0xE0
+    [0x0A, 0x00, 0x0E, 0x01, 0x0F, 0x11, 0x0F, 0x00], // 0xE1
+    [0x00, 0x00, 0x0E, 0x11, 0x1E, 0x11, 0x1E, 0x10], // 0xE2
+    [0x00, 0x00, 0x0E, 0x10, 0x0C, 0x11, 0x0E, 0x00], // 0xE3
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x13, 0x1D, 0x10], // 0xE4
+    [0x00, 0x00, 0x0F, 0x14, 0x11, 0x11, 0x0E, 0x00], // 0xE5
+    [0x00, 0x00, 0x0C, 0x12, 0x11, 0x11, 0x1E, 0x10], // 0xE6
+    [0x00, 0x00, 0x0F, 0x11, 0x11, 0x11, 0x0F, 0x01], // 0xE7
+    [0x00, 0x00, 0x07, 0x04, 0x04, 0x14, 0x08, 0x00], // 0xE8
+    [0x00, 0x02, 0x1A, 0x02, 0x02, 0x00, 0x00, 0x00], // 0xE9
+    [0x00, 0x02, 0x00, 0x06, 0x02, 0x02, 0x02, 0x12], // 0xEA
+    [0x00, 0x14, 0x08, 0x14, 0x00, 0x00, 0x00, 0x00], // 0xEB
+    [0x00, 0x04, 0x0F, 0x14, 0x14, 0x0F, 0x04, 0x00], // 0xEC
+    [0x00, 0x08, 0x1C, 0x08, 0x1C, 0x08, 0x0F, 0x00], // 0xED
+    [0x0E, 0x00, 0x16, 0x19, 0x11, 0x11, 0x11, 0x00], // 0xEE
+    [0x0A, 0x00, 0x0E, 0x11, 0x11, 0x11, 0x0E, 0x00], // 0xEF
+    [0x00, 0x00, 0x16, 0x19, 0x11, 0x11, 0x1E, 0x10], // 0xF0
+    [0x00, 0x00, 0x0D, 0x13, 0x11, 0x11, 0x0F, 0x01], // 0xF1
+    [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x0E, 0x00], // 0xF2
+    [0x00, 0x00, 0x00, 0x0B, 0x15, 0x1A, 0x00, 0x00], // 0xF3
+    [0x00, 0x0E, 0x11, 0x11, 0x11, 0x0A, 0x1B, 0x00], // 0xF4
+    [0x0A, 0x00, 0x11, 0x11, 0x11, 0x13, 0x0D, 0x00], // 0xF5
+    [0x1F, 0x10, 0x08, 0x04, 0x08, 0x10, 0x1F, 0x00], // 0xF6
+    [0x00, 0x00, 0x1F, 0x0A, 0x0A, 0x0A, 0x13, 0x00], // 0xF7
+    [0x1F, 0x00, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x00], // 0xF8
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x11, 0x0F, 0x01], // 0xF9
+    [0x00, 0x01, 0x1E, 0x04, 0x1F, 0x04, 0x04, 0x00], // 0xFA
+    [0x00, 0x00, 0x1F, 0x08, 0x0F, 0x09, 0x11, 0x00], // 0xFB
+    [0x00, 0x1F, 0x15, 0x1F, 0x11, 0x11, 0x11, 0x00], // 0xFC
+    [0x00, 0x04, 0x00, 0x1F, 0x00, 0x04, 0x00, 0x00], // 0xFD
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0xFE
+    [0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F], // 0xFF
+];
+
+const A02: [[u8; 8]; 240] = [
+    [0x08, 0x0C, 0x0E, 0x0F, 0x0E, 0x0C, 0x08, 0x00], // 0x10
+    [0x02, 0x06, 0x0E, 0x1E, 0x0E, 0x06, 0x02, 0x00], // 0x11
+    [0x09, 0x12, 0x1B, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x12
+    [0x1B, 0x09, 0x12, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x13
+    [0x04, 0x0E, 0x1F, 0x00, 0x04, 0x0E, 0x1F, 0x00], // 0x14
+    [0x1F, 0x0E, 0x04, 0x00, 0x1F, 0x0E, 0x04, 0x00], // 0x15
+    [0x00, 0x0E, 0x1F, 0x1F, 0x1F, 0x0E, 0x00, 0x00], // 0x16
+    [0x01, 0x01, 0x05, 0x09, 0x1F, 0x08, 0x04, 0x00], // 0x17
+    [0x04, 0x0E, 0x15, 0x04, 0x04, 0x04, 0x04, 0x00], // 0x18
+    [0x04, 0x04, 0x04, 0x04, 0x15, 0x0E, 0x04, 0x00], // 0x19
+    [0x00, 0x04, 0x02, 0x1F, 0x02, 0x04, 0x00, 0x00], // 0x1A
+    [0x00, 0x04, 0x08, 0x1F, 0x08, 0x04, 0x00, 0x00], // 0x1B
+    [0x02, 0x04, 0x08, 0x04, 0x02, 0x00, 0x1F, 0x00], // 0x1C
+    [0x08, 0x04, 0x02, 0x04, 0x08, 0x00, 0x1F, 0x00], // 0x1D
+    [0x00, 0x04, 0x04, 0x0E, 0x0E, 0x1F, 0x00, 0x00], // 0x1E
+    [0x00, 0x1F, 0x0E, 0x0E, 0x04, 0x04, 0x00, 0x00], // 0x1F
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x20
+    [0x04, 0x04, 0x04, 0x04, 0x00, 0x00, 0x04, 0x00], // 0x21
+    [0x0A, 0x0A, 0x0A, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x22
+    [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A, 0x00], // 0x23
+    [0x04, 0x0F, 0x14, 0x0E, 0x05, 0x1E, 0x04, 0x00], // 0x24
+    [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03, 0x00], // 0x25
+    [0x0C, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0D, 0x00], // 0x26
+    [0x0C, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x27
+    [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02, 0x00], // 0x28
+    [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08, 0x00], // 0x29
+    [0x00, 0x04, 0x15, 0x0E, 0x15, 0x04, 0x00, 0x00], // 0x2A
+    [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00, 0x00], // 0x2B
+    [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08, 0x00], // 0x2C
+    [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00, 0x00], // 0x2D
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x00], // 0x2E
+    [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00, 0x00], // 0x2F
+    [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E, 0x00], // 0x30
+    [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E, 0x00], // 0x31
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F, 0x00], // 0x32
+    [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E, 0x00], // 0x33
+    [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02, 0x00], // 0x34
+    [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E, 0x00], // 0x35
+    [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E, 0x00], // 0x36
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08, 0x00], // 0x37
+    [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E, 0x00], // 0x38
+    [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C, 0x00], // 0x39
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00, 0x00], // 0x3A
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x04, 0x08, 0x00], // 0x3B
+    [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02, 0x00], // 0x3C
+    [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00, 0x00], // 0x3D
+    [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08, 0x00], // 0x3E
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04, 0x00], // 0x3F
+    [0x0E, 0x11, 0x01, 0x0D, 0x15, 0x15, 0x0E, 0x00], // 0x40
+    [0x0E, 0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x00], // 0x41
+    [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E, 0x00], // 0x42
+    [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E, 0x00], // 0x43
+    [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C, 0x00], // 0x44
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F, 0x00], // 0x45
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10, 0x00], // 0x46
+    [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F, 0x00], // 0x47
+    [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11, 0x00], // 0x48
+    [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E, 0x00], // 0x49
+    [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C, 0x00], // 0x4A
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11, 0x00], // 0x4B
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F, 0x00], // 0x4C
+    [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11, 0x00], // 0x4D
+    [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11, 0x00], // 0x4E
+    [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E, 0x00], // 0x4F
+    [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10, 0x00], // 0x50
+    [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D, 0x00], // 0x51
+    [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11, 0x00], // 0x52
+    [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E, 0x00], // 0x53
+    [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x00], // 0x54
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E, 0x00], // 0x55
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04, 0x00], // 0x56
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A, 0x00], // 0x57
+    [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11, 0x00], // 0x58
+    [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04, 0x00], // 0x59
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F, 0x00], // 0x5A
+    [0x0E, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0E, 0x00], // 0x5B
+    [0x00, 0x10, 0x08, 0x04, 0x02, 0x01, 0x00, 0x00], // 0x5C
+    [0x0E, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0E, 0x00], // 0x5D
+    [0x04, 0x0A, 0x11, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x5E
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F, 0x00], // 0x5F
+    [0x08, 0x04, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00], // 0x60
+    [0x00, 0x00, 0x0E, 0x01, 0x0F, 0x11, 0x0F, 0x00], // 0x61
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x1E, 0x00], // 0x62
+    [0x00, 0x00, 0x0E, 0x10, 0x10, 0x11, 0x0E, 0x00], // 0x63
+    [0x01, 0x01, 0x0D, 0x13, 0x11, 0x11, 0x0F, 0x00], // 0x64
+    [0x00, 0x00, 0x0E, 0x11, 0x1F, 0x10, 0x0E, 0x00], // 0x65
+    [0x06, 0x09, 0x08, 0x1C, 0x08, 0x08, 0x08, 0x00], // 0x66
+    [0x00, 0x0F, 0x11, 0x11, 0x0F, 0x01, 0x0E, 0x00], // 0x67
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x11, 0x00], // 0x68
+    [0x04, 0x00, 0x0C, 0x04, 0x04, 0x04, 0x0E, 0x00], // 0x69
+    [0x02, 0x00, 0x06, 0x02, 0x02, 0x12, 0x0C, 0x00], // 0x6A
+    [0x10, 0x10, 0x12, 0x14, 0x18, 0x14, 0x12, 0x00], // 0x6B
+    [0x0C, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E, 0x00], // 0x6C
+    [0x00, 0x00, 0x1A, 0x15, 0x15, 0x11, 0x11, 0x00], // 0x6D
+    [0x00, 0x00, 0x16, 0x19, 0x11, 0x11, 0x11, 0x00], // 0x6E
+    [0x00, 0x00, 0x0E, 0x11, 0x11, 0x11, 0x0E, 0x00], // 0x6F
+    [0x00, 0x00, 0x1E, 0x11, 0x1E, 0x10, 0x10, 0x00], // 0x70
+    [0x00, 0x00, 0x0D, 0x13, 0x0F, 0x01, 0x01, 0x00], // 0x71
+    [0x00, 0x00, 0x16, 0x19, 0x10, 0x10, 0x10, 0x00], // 0x72
+    [0x00, 0x00, 0x0E, 0x10, 0x0E, 0x01, 0x1E, 0x00], // 0x73
+    [0x08, 0x08, 0x1C, 0x08, 0x08, 0x09, 0x06, 0x00], // 0x74
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x13, 0x0D, 0x00], // 0x75
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x0A, 0x04, 0x00], // 0x76
+    [0x00, 0x00, 0x11, 0x11, 0x15, 0x15, 0x0A, 0x00], // 0x77
+    [0x00, 0x00, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x00], // 0x78
+    [0x00, 0x00, 0x11, 0x11, 0x0F, 0x01, 0x0E, 0x00], // 0x79
+    [0x00, 0x00, 0x1F, 0x02, 0x04, 0x08, 0x1F, 0x00], // 0x7A
+    [0x02, 0x04, 0x04, 0x08, 0x04, 0x04, 0x02, 0x00], // 0x7B
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x00], // 0x7C
+    [0x08, 0x04, 0x04, 0x02, 0x04, 0x04, 0x08, 0x00], // 0x7D
+    [0x00, 0x00, 0x0D, 0x12, 0x00, 0x00, 0x00, 0x00], // 0x7E
+    [0x04, 0x0A, 0x11, 0x11, 0x11, 0x11, 0x1F, 0x00], // 0x7F
+    [0x1F, 0x10, 0x10, 0x1E, 0x11, 0x11, 0x1E, 0x00], // 0x80
+    [0x06, 0x0A, 0x0A, 0x0A, 0x0A, 0x1F, 0x11, 0x00], // 0x81
+    [0x15, 0x15, 0x0E, 0x04, 0x0E, 0x15, 0x15, 0x00], // 0x82
+    [0x0E, 0x11, 0x01, 0x06, 0x01, 0x11, 0x0E, 0x00], // 0x83
+    [0x11, 0x11, 0x13, 0x15, 0x19, 0x11, 0x11, 0x00], // 0x84
+    [0x0A, 0x04, 0x11, 0x13, 0x15, 0x19, 0x11, 0x00], // 0x85
+    [0x07, 0x09, 0x09, 0x09, 0x09, 0x09, 0x11, 0x00], // 0x86
+    [0x1F, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x00], // 0x87
+    [0x11, 0x11, 0x11, 0x0F, 0x01, 0x11, 0x0E, 0x00], // 0x88
+    [0x12, 0x12, 0x12, 0x12, 0x12, 0x1F, 0x01, 0x00], // 0x89
+    [0x11, 0x11, 0x11, 0x0F, 0x01, 0x01, 0x01, 0x00], // 0x8A
+    [0x15, 0x15, 0x15, 0x15, 0x15, 0x15, 0x1F, 0x00], // 0x8B
+    [0x15, 0x15, 0x15, 0x15, 0x15, 0x1F, 0x01, 0x00], // 0x8C
+    [0x18, 0x08, 0x08, 0x0E, 0x09, 0x09, 0x0E, 0x00], // 0x8D
+    [0x11, 0x11, 0x11, 0x1D, 0x15, 0x15, 0x1D, 0x00], // 0x8E
+    [0x0E, 0x11, 0x01, 0x07, 0x01, 0x11, 0x0E, 0x00], // 0x8F
+    [0x00, 0x00, 0x09, 0x15, 0x12, 0x12, 0x0D, 0x00], // 0x90
+    [0x04, 0x06, 0x05, 0x04, 0x0C, 0x1C, 0x08, 0x00], // 0x91
+    [0x1F, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x00], // 0x92
+    [0x00, 0x00, 0x1F, 0x0A, 0x0A, 0x0A, 0x13, 0x00], // 0x93
+    [0x1F, 0x10, 0x08, 0x04, 0x08, 0x10, 0x1F, 0x00], // 0x94
+    [0x00, 0x00, 0x0F, 0x14, 0x11, 0x11, 0x0E, 0x00], // 0x95
+    [0x03, 0x05, 0x05, 0x04, 0x0C, 0x1C, 0x08, 0x00], // 0x96
+    [0x00, 0x00, 0x1F, 0x04, 0x04, 0x04, 0x03, 0x00], // 0x97
+    [0x04, 0x0E, 0x0E, 0x0E, 0x1F, 0x00, 0x04, 0x00], // 0x98
+    [0x0E, 0x11, 0x11, 0x15, 0x11, 0x11, 0x0E, 0x00], // 0x99
+    [0x00, 0x0E, 0x11, 0x11, 0x11, 0x0A, 0x1B, 0x00], // 0x9A
+    [0x06, 0x08, 0x04, 0x0E, 0x11, 0x11, 0x0E, 0x00], // 0x9B
+    [0x00, 0x00, 0x00, 0x0B, 0x15, 0x1A, 0x00, 0x00], // 0x9C
+    [0x00, 0x0A, 0x1F, 0x1F, 0x0E, 0x04, 0x00, 0x00], // 0x9D
+    [0x00, 0x00, 0x0E, 0x10, 0x0C, 0x11, 0x0E, 0x00], // 0x9E
+    [0x00, 0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x00], // 0x9F
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // 0xA0
+    [0x04, 0x00, 0x04, 0x04, 0x04, 0x04, 0x04, 0x00], // 0xA1
+    [0x04, 0x0E, 0x14, 0x14, 0x15, 0x0E, 0x04, 0x00], // 0xA2
+    [0x06, 0x09, 0x08, 0x1C, 0x08, 0x09, 0x16, 0x00], // 0xA3
+    [0x00, 0x11, 0x0E, 0x0A, 0x0E, 0x11, 0x00, 0x00], // 0xA4
+    [0x11, 0x0A, 0x1F, 0x04, 0x1F, 0x04, 0x04, 0x00], // 0xA5
+    [0x04, 0x04, 0x04, 0x00, 0x04, 0x04, 0x04, 0x00], // 0xA6
+    [0x0E, 0x10, 0x0E, 0x11, 0x0E, 0x01, 0x0E, 0x00], // 0xA7
+    [0x03, 0x04, 0x04, 0x0E, 0x04, 0x04, 0x18, 0x00], // 0xA8
+    [0x0E, 0x11, 0x15, 0x19, 0x15, 0x11, 0x0E, 0x00], // 0xA9
+    [0x0E, 0x01, 0x0F, 0x11, 0x0F, 0x00, 0x1F, 0x00], // 0xAA
+    [0x00, 0x05, 0x0A, 0x14, 0x0A, 0x05, 0x00, 0x00], // 0xAB
+    [0x12, 0x15, 0x15, 0x1D, 0x15, 0x15, 0x12, 0x00], // 0xAC
+    [0x0F, 0x11, 0x11, 0x0F, 0x05, 0x09, 0x11, 0x00], // 0xAD
+    [0x0E, 0x11, 0x1D, 0x19, 0x15, 0x11, 0x0E, 0x00], // 0xAE
+    [0x04, 0x08, 0x0C, 0x00, 0x00, 0x00, 0x00, 0x00], // 0xAF
+    [0x0C, 0x12, 0x12, 0x0C, 0x00, 0x00, 0x00, 0x00], // 0xB0
+    [0x04, 0x04, 0x1F, 0x04, 0x04, 0x00, 0x1F, 0x00], // 0xB1
+    [0x0C, 0x12, 0x04, 0x08, 0x1E, 0x00, 0x00, 0x00], // 0xB2
+    [0x1C, 0x02, 0x0C, 0x02, 0x1C, 0x00, 0x00, 0x00], // 0xB3
+    [0x1C, 0x14, 0x1C, 0x12, 0x17, 0x12, 0x13, 0x00], // 0xB4
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x13, 0x1D, 0x00], // 0xB5
+    [0x0F, 0x1D, 0x1D, 0x0D, 0x05, 0x05, 0x05, 0x00], // 0xB6
+    [0x00, 0x00, 0x00, 0x0C, 0x0C, 0x00, 0x00, 0x00], // 0xB7
+    [0x00, 0x00, 0x00, 0x11, 0x15, 0x15, 0x0A, 0x00], // 0xB8
+    [0x08, 0x18, 0x08, 0x08, 0x1C, 0x00, 0x00, 0x00], // 0xB9
+    [0x0E, 0x11, 0x11, 0x0E, 0x00, 0x1F, 0x00, 0x00], // 0xBA
+    [0x00, 0x14, 0x0A, 0x05, 0x0A, 0x14, 0x00, 0x00], // 0xBB
+    [0x11, 0x12, 0x14, 0x0B, 0x15, 0x07, 0x01, 0x00], // 0xBC
+    [0x11, 0x12, 0x14, 0x0B, 0x11, 0x02, 0x07, 0x00], // 0xBD
+    [0x18, 0x08, 0x19, 0x0C, 0x15, 0x07, 0x01, 0x00], // 0xBE
+    [0x04, 0x00, 0x04, 0x08, 0x10, 0x11, 0x0E, 0x00], // 0xBF
+    [0x08, 0x04, 0x0E, 0x11, 0x1F, 0x11, 0x11, 0x00], // 0xC0
+    [0x02, 0x04, 0x0E, 0x11, 0x1F, 0x11, 0x11, 0x00], // 0xC1
+    [0x04, 0x0A, 0x0E, 0x11, 0x1F, 0x11, 0x11, 0x00], // 0xC2
+    [0x09, 0x16, 0x0E, 0x11, 0x1F, 0x11, 0x11, 0x00], // 0xC3
+    [0x0A, 0x00, 0x0E, 0x11, 0x1F, 0x11, 0x11, 0x00], // 0xC4
+    [0x04, 0x0A, 0x04, 0x0E, 0x11, 0x1F, 0x11, 0x00], // 0xC5
+    [0x0F, 0x14, 0x14, 0x1F, 0x14, 0x14, 0x17, 0x00], // 0xC6
+    [0x0E, 0x11, 0x10, 0x10, 0x11, 0x0E, 0x04, 0x00], // 0xC7
+    [0x08, 0x04, 0x1F, 0x10, 0x1E, 0x10, 0x1F, 0x00], // 0xC8
+    [0x02, 0x04, 0x1F, 0x10, 0x1E, 0x10, 0x1F, 0x00], // 0xC9
+    [0x04, 0x0A, 0x1F, 0x10, 0x1E, 0x10, 0x1F, 0x00], // 0xCA
+    [0x0A, 0x00, 0x1F, 0x10, 0x1E, 0x10, 0x1F, 0x00], // 0xCB
+    [0x08, 0x04, 0x0E, 0x04, 0x04, 0x04, 0x0E, 0x00], // 0xCC
+    [0x02, 0x04, 0x0E, 0x04, 0x04, 0x04, 0x0E, 0x00], // 0xCD
+    [0x04, 0x0A, 0x0E, 0x04, 0x04, 0x04, 0x0E, 0x00], // 0xCE
+    [0x0A, 0x00, 0x0E, 0x04, 0x04, 0x04, 0x0E, 0x00], // 0xCF
+    [0x1C, 0x12, 0x11, 0x1D, 0x11, 0x12, 0x1C, 0x00], // 0xD0
+    [0x09, 0x16, 0x11, 0x19, 0x15, 0x13, 0x11, 0x00], // 0xD1
+    [0x08, 0x04, 0x0E, 0x11, 0x11, 0x11, 0x0E, 0x00], // 0xD2
+    [0x02, 0x04, 0x0E, 0x11, 0x11, 0x11, 0x0E, 0x00], // 0xD3
+    [0x04, 0x0A, 0x0E, 0x11, 0x11, 0x11, 0x0E, 0x00], // 0xD4
+    [0x09, 0x16, 0x0E, 0x11, 0x11, 0x11, 0x0E, 0x00], // 0xD5
+    [0x0A, 0x00, 0x0E, 0x11, 0x11, 0x11, 0x0E, 0x00], // 0xD6
+    [0x00, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x00, 0x00], // 0xD7
+    [0x0F, 0x13, 0x15, 0x15, 0x15, 0x19, 0x1E, 0x00], // 0xD8
+    [0x08, 0x04, 0x11, 0x11, 0x11, 0x11, 0x0E, 0x00], // 0xD9
+    [0x02, 0x04, 0x11, 0x11, 0x11, 0x11, 0x0E, 0x00], // 0xDA
+    [0x04, 0x0A, 0x11, 0x11, 0x11, 0x11, 0x0E, 0x00], // 0xDB
+    [0x0A, 0x00, 0x11, 0x11, 0x11, 0x11, 0x0E, 0x00], // 0xDC
+    [0x02, 0x04, 0x11, 0x0A, 0x04, 0x04, 0x04, 0x00], // 0xDD
+    [0x10, 0x1E, 0x11, 0x11, 0x11, 0x1E, 0x10, 0x00], // 0xDE
+    [0x0C, 0x12, 0x12, 0x14, 0x12, 0x11, 0x16, 0x00], // 0xDF
+    [0x08, 0x04, 0x0E, 0x01, 0x0F, 0x11, 0x0F, 0x00], // 0xE0
+    [0x02, 0x04, 0x0E, 0x01, 0x0F, 0x11, 0x0F, 0x00], // 0xE1
+    [0x04, 0x0A, 0x0E, 0x01, 0x0F, 0x11, 0x0F, 0x00], // 0xE2
+    [0x09, 0x16, 0x0E, 0x01, 0x0F, 0x11, 0x0F, 0x00], // 0xE3
+    [0x0A, 0x00, 0x0E, 0x01, 0x0F, 0x11, 0x0F, 0x00], // 0xE4
+    [0x04, 0x0A, 0x04, 0x01, 0x0F, 0x11, 0x0F, 0x00], // 0xE5
+    [0x00, 0x00, 0x1A, 0x05, 0x0F, 0x14, 0x0F, 0x00], // 0xE6
+    [0x00, 0x0E, 0x10, 0x10, 0x11, 0x0E, 0x04, 0x00], // 0xE7
+    [0x08, 0x04, 0x0E, 0x11, 0x1F, 0x10, 0x0E, 0x00], // 0xE8
+    [0x02, 0x04, 0x0E, 0x11, 0x1F, 0x10, 0x0E, 0x00], // 0xE9
+    [0x04, 0x0A, 0x0E, 0x11, 0x1F, 0x10, 0x0E, 0x00], // 0xEA
+    [0x0A, 0x00, 0x0E, 0x11, 0x1F, 0x10, 0x0E, 0x00], // 0xEB
+    [0x08, 0x04, 0x0C, 0x04, 0x04, 0x04, 0x0E, 0x00], // 0xEC
+    [0x02, 0x04, 0x0C, 0x04, 0x04, 0x04, 0x0E, 0x00], // 0xED
+    [0x04, 0x0A, 0x0C, 0x04, 0x04, 0x04, 0x0E, 0x00], // 0xEE
+    [0x0A, 0x00, 0x0C, 0x04, 0x04, 0x04, 0x0E, 0x00], // 0xEF
+    [0x0A, 0x04, 0x0A, 0x0E, 0x11, 0x11, 0x0E, 0x00], // 0xF0
+    [0x09, 0x16, 0x16, 0x19, 0x11, 0x11, 0x11, 0x00], // 0xF1
+    [0x08, 0x04, 0x0E, 0x11, 0x11, 0x11, 0x0E, 0x00], // 0xF2
+    [0x02, 0x04, 0x0E, 0x11, 0x11, 0x11, 0x0E, 0x00], // 0xF3
+    [0x04, 0x0A, 0x0E, 0x11, 0x11, 0x11, 0x0E, 0x00], // 0xF4
+    [0x09, 0x16, 0x0E, 0x11, 0x11, 0x11, 0x0E, 0x00], // 0xF5
+    [0x0A, 0x00, 0x0E, 0x11, 0x11, 0x11, 0x0E, 0x00], // 0xF6
+    [0x00, 0x04, 0x00, 0x1F, 0x00, 0x04, 0x00, 0x00], // 0xF7
+    [0x00, 0x00, 0x0E, 0x13, 0x15, 0x19, 0x0E, 0x00], // 0xF8
+    [0x08, 0x04, 0x11, 0x11, 0x11, 0x13, 0x0D, 0x00], // 0xF9
+    [0x02, 0x04, 0x11, 0x11, 0x11, 0x13, 0x0D, 0x00], // 0xFA
+    [0x04, 0x0A, 0x11, 0x11, 0x11, 0x13, 0x0D, 0x00], // 0xFB
+    [0x0A, 0x00, 0x11, 0x11, 0x11, 0x13, 0x0D, 0x00], // 0xFC
+    [0x02, 0x04, 0x11, 0x11, 0x0F, 0x01, 0x0E, 0x00], // 0xFD
+    [0x00, 0x10, 0x1E, 0x11, 0x1E, 0x10, 0x10, 0x00], // 0xFE
+    [0x0A, 0x00, 0x11, 0x11, 0x0F, 0x01, 0x0E, 0x00], // 0xFF
+];
+
+#[cfg(test)]
+mod test {
+    use crate::font::*;
+
+    #[test]
+    fn rom_test() {
+        assert_eq!(Rom::A00.glyph(0x07), None);
+        assert_eq!(Rom::A00.glyph(b' '), Some([0; 8]));
+        assert_eq!(Rom::A00.glyph(b'-'), Some([0, 0, 0, 0x1F, 0, 0, 0, 0]));
+        // The two ROMs differ at `\`, the yen sign is on the A00
+        assert_ne!(Rom::A00.glyph(b'\\'), Rom::A02.glyph(b'\\'));
+        assert_eq!(Rom::A00.glyph(b'A'), Rom::A02.glyph(b'A'));
+        assert_eq!(Rom::A00.encode('ö'), Some(0xEF));
+        assert_eq!(Rom::A02.encode('ö'), Some(0xF6));
+        assert_eq!(Rom::A02.encode('Я'), Some(0xAD));
+        assert_eq!(Rom::A02.encode('ｱ'), None);
+        assert_eq!(Rom::A00.decode(0xB1), Some('ｱ'));
+        assert_eq!(Rom::A02.decode(b'\\'), Some('\\'));
+        for code in 0x10..=0xFF {
+            if let Some(c) = Rom::A02.decode(code) {
+                assert_eq!(Rom::A02.encode(c), Some(code));
+            }
+        }
+    }
+}
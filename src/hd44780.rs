@@ -0,0 +1,726 @@
+// The HD44780 instruction set, init sequence and character mapping, shared by every backend. Only
+// needs `core` so it can drive a display from a microcontroller through `embedded_hal` pins
+// Adapted from Arduino standard library LiquidCrystal.cpp/h
+
+use crate::icons::Icon;
+use embedded_hal::blocking::delay::DelayUs;
+use embedded_hal::digital::v2::{InputPin, OutputPin};
+
+// Commands
+pub const LCD_CLEAR_DISPLAY: u8 = 0x01;
+pub const LCD_RETURN_HOME: u8 = 0x02;
+pub const LCD_ENTRY_MODE_SET: u8 = 0x04;
+pub const LCD_DISPLAY_CONTROL: u8 = 0x08;
+pub const LCD_CURSOR_SHIFT: u8 = 0x10;
+pub const LCD_FUNCTION_SET: u8 = 0x20;
+pub const LCD_SET_CGRAM_ADDR: u8 = 0x40;
+pub const LCD_SET_DDRAM_ADDR: u8 = 0x80;
+
+// Display entry mode
+pub const LCD_ENTRY_LEFT: u8 = 0x02;
+pub const LCD_ENTRY_SHIFT_DECREMENT: u8 = 0x00;
+
+// Display on/off control
+pub const LCD_DISPLAY_ON: u8 = 0x04;
+pub const LCD_CURSOR_ON: u8 = 0x02;
+pub const LCD_CURSOR_OFF: u8 = 0x00;
+pub const LCD_BLINK_ON: u8 = 0x01;
+pub const LCD_BLINK_OFF: u8 = 0x00;
+
+// Display/cursor shift
+pub const LCD_LEFT: u8 = 0x00;
+pub const LCD_CURSOR_MOVE: u8 = 0x00;
+pub const LCD_RIGHT: u8 = 0x04;
+pub const LCD_DISPLAY_MOVE: u8 = 0x08;
+
+// Function setting
+pub const LCD_4BITMODE: u8 = 0x00;
+pub const LCD_8BITMODE: u8 = 0x10;
+pub const LCD_1LINE: u8 = 0x00;
+pub const LCD_2LINE: u8 = 0x08;
+pub const LCD_5X8DOTS: u8 = 0x00;
+
+// The longest a clear or home takes, and the most `wait_busy` waits before giving up
+const LONG_COMMAND_US: u32 = 2000;
+const BUSY_POLL_US: u32 = 10;
+const BUSY_TIMEOUT_US: u32 = 10_000;
+
+// Gets bits onto the controller's data lines, everything above this is the same for any backend
+pub trait Bus {
+    type Error;
+
+    // Decides the init sequence and whether bytes go out whole or as two nibbles
+    fn is_eight_bit(&self) -> bool;
+
+    // Latch one transfer into the controller, a whole byte on an 8 bit bus or the low nibble on a
+    // 4 bit bus. `data` picks the data register over the instruction register (the RS line)
+    fn write_bits(&mut self, bits: u8, data: bool) -> Result<(), Self::Error>;
+
+    fn delay_us(&mut self, us: u32);
+
+    // Whether the controller is still working on the last instruction, None if the bus can't read
+    // from it. Buses that can let the driver wait on this instead of fixed delays
+    fn is_busy(&mut self) -> Result<Option<bool>, Self::Error> {
+        Ok(None)
+    }
+}
+
+pub enum DataPins<P> {
+    // D4 to D7
+    Four([P; 4]),
+    // D0 to D7
+    Eight([P; 8]),
+}
+
+// Where `PinBus` reads the busy flag from, which is any `InputPin` wired to D7
+pub trait BusyPin<E> {
+    fn is_connected(&self) -> bool;
+
+    fn is_high(&self) -> Result<bool, E>;
+}
+
+// For a `PinBus` that only writes, which is the default
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoBusyPin;
+
+// A bus made of GPIO pins, which all need to be the same type. Most HALs can erase their pin
+// types to allow this
+pub struct PinBus<P, D, I = NoBusyPin> {
+    rs: P,
+    // Held low, unless there is a busy pin and the busy flag is being read
+    rw: Option<P>,
+    enable: P,
+    data: DataPins<P>,
+    delay: D,
+    busy: I,
+}
+
+pub struct Hd44780<B> {
+    bus: B,
+    disp_func: u8,
+    disp_mode: u8,
+    disp_control: u8,
+    num_cols: u8,
+    num_rows: u8,
+    row_offsets: [u8; 4],
+}
+
+impl<P> DataPins<P> {
+    pub fn map<Q, F: FnMut(P) -> Q>(self, f: F) -> DataPins<Q> {
+        match self {
+            DataPins::Four(pins) => DataPins::Four(pins.map(f)),
+            DataPins::Eight(pins) => DataPins::Eight(pins.map(f)),
+        }
+    }
+}
+
+impl<P: OutputPin, D: DelayUs<u32>> PinBus<P, D> {
+    pub fn new(rs: P, rw: Option<P>, enable: P, data: DataPins<P>, delay: D) -> Self {
+        PinBus {
+            rs,
+            rw,
+            enable,
+            data,
+            delay,
+            busy: NoBusyPin,
+        }
+    }
+
+    // Read the busy flag from `busy`, an input wired to D7, so clears and homes don't have to
+    // wait the worst case time. Needs the RW line. While reading, the data lines are set high,
+    // so they have to be open drain outputs (with pull ups) to let the controller drive D7
+    pub fn with_busy_pin<I: BusyPin<P::Error>>(self, busy: I) -> PinBus<P, D, I> {
+        PinBus {
+            rs: self.rs,
+            rw: self.rw,
+            enable: self.enable,
+            data: self.data,
+            delay: self.delay,
+            busy,
+        }
+    }
+}
+
+impl<P: OutputPin, D: DelayUs<u32>, I> PinBus<P, D, I> {
+    pub fn release(self) -> (P, Option<P>, P, DataPins<P>, D) {
+        (self.rs, self.rw, self.enable, self.data, self.delay)
+    }
+
+    pub fn release_busy_pin(self) -> (PinBus<P, D>, I) {
+        let bus = PinBus::new(self.rs, self.rw, self.enable, self.data, self.delay);
+        (bus, self.busy)
+    }
+
+    fn pulse_enable(&mut self) -> Result<(), P::Error> {
+        self.enable.set_low()?;
+        self.delay.delay_us(10);
+        self.enable.set_high()?;
+        self.delay.delay_us(10);
+        self.enable.set_low()?;
+        self.delay.delay_us(100);
+        Ok(())
+    }
+}
+
+impl<P: OutputPin, D: DelayUs<u32>, I: BusyPin<P::Error>> Bus for PinBus<P, D, I> {
+    type Error = P::Error;
+
+    fn is_eight_bit(&self) -> bool {
+        match self.data {
+            DataPins::Four(_) => false,
+            DataPins::Eight(_) => true,
+        }
+    }
+
+    fn write_bits(&mut self, bits: u8, data: bool) -> Result<(), P::Error> {
+        set(&mut self.rs, data)?;
+        if let Some(rw) = self.rw.as_mut() {
+            rw.set_low()?;
+        }
+        let pins: &mut [P] = match &mut self.data {
+            DataPins::Four(pins) => pins,
+            DataPins::Eight(pins) => pins,
+        };
+        for (i, pin) in pins.iter_mut().enumerate() {
+            set(pin, (bits >> i) & 0x01 != 0)?;
+        }
+        self.pulse_enable()
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        self.delay.delay_us(us);
+    }
+
+    fn is_busy(&mut self) -> Result<Option<bool>, P::Error> {
+        if !self.busy.is_connected() {
+            return Ok(None);
+        }
+        let rw = match self.rw.as_mut() {
+            Some(rw) => rw,
+            None => return Ok(None),
+        };
+        self.rs.set_low()?;
+        rw.set_high()?;
+        let (pins, reads): (&mut [P], u8) = match &mut self.data {
+            DataPins::Four(pins) => (pins, 2),
+            DataPins::Eight(pins) => (pins, 1),
+        };
+        for pin in pins.iter_mut() {
+            pin.set_high()?;
+        }
+        // The flag comes with the high nibble, the low one still has to be clocked out
+        let mut busy = false;
+        for read in 0..reads {
+            self.enable.set_high()?;
+            self.delay.delay_us(1);
+            if read == 0 {
+                busy = self.busy.is_high()?;
+            }
+            self.enable.set_low()?;
+            self.delay.delay_us(1);
+        }
+        if let Some(rw) = self.rw.as_mut() {
+            rw.set_low()?;
+        }
+        Ok(Some(busy))
+    }
+}
+
+impl<I: InputPin> BusyPin<I::Error> for I {
+    fn is_connected(&self) -> bool {
+        true
+    }
+
+    fn is_high(&self) -> Result<bool, I::Error> {
+        InputPin::is_high(self)
+    }
+}
+
+impl<E> BusyPin<E> for NoBusyPin {
+    fn is_connected(&self) -> bool {
+        false
+    }
+
+    fn is_high(&self) -> Result<bool, E> {
+        Ok(false)
+    }
+}
+
+fn set<P: OutputPin>(pin: &mut P, high: bool) -> Result<(), P::Error> {
+    if high {
+        pin.set_high()
+    } else {
+        pin.set_low()
+    }
+}
+
+impl<B: Bus> Hd44780<B> {
+    // Nothing is sent until `init`
+    pub fn new(bus: B, cols: u8, rows: u8) -> Self {
+        let mut disp_func = if bus.is_eight_bit() {
+            LCD_8BITMODE | LCD_1LINE | LCD_5X8DOTS
+        } else {
+            LCD_4BITMODE | LCD_1LINE | LCD_5X8DOTS
+        };
+        if rows > 1 {
+            disp_func |= LCD_2LINE;
+        }
+        Hd44780 {
+            bus,
+            disp_func,
+            disp_mode: LCD_ENTRY_LEFT | LCD_ENTRY_SHIFT_DECREMENT,
+            disp_control: LCD_DISPLAY_ON | LCD_CURSOR_OFF | LCD_BLINK_OFF,
+            num_cols: cols,
+            num_rows: rows,
+            row_offsets: [0x00, 0x40, cols, 0x40 + cols],
+        }
+    }
+
+    // Runs the controller's power on sequence, which also clears the display and loads the icons
+    // into CGRAM. Safe to call again on a display that has gotten out of sync
+    pub fn init(&mut self) -> Result<(), B::Error> {
+        if (self.disp_func & LCD_8BITMODE) == 0 {
+            self.bus.write_bits(0x03, false)?;
+            self.bus.delay_us(4500);
+
+            self.bus.write_bits(0x03, false)?;
+            self.bus.delay_us(4500);
+
+            self.bus.write_bits(0x03, false)?;
+            self.bus.delay_us(150);
+
+            self.bus.write_bits(0x02, false)?;
+        } else {
+            self.command(LCD_FUNCTION_SET | self.disp_func)?;
+            self.bus.delay_us(4500);
+
+            self.command(LCD_FUNCTION_SET | self.disp_func)?;
+            self.bus.delay_us(150);
+
+            self.command(LCD_FUNCTION_SET | self.disp_func)?;
+        }
+
+        self.command(LCD_FUNCTION_SET | self.disp_func)?;
+        self.display()?;
+        self.clear()?;
+
+        self.command(LCD_ENTRY_MODE_SET | self.disp_mode)?;
+
+        for icon in [
+            Icon::MAIL,
+            Icon::BELL,
+            Icon::FILLEDBOX,
+            Icon::EMPTYBOX,
+            Icon::MUSIC,
+            Icon::PLAY,
+            Icon::PAUSE,
+        ]
+        .iter()
+        {
+            self.create_char(icon.index(), icon.char_data())?;
+        }
+        // Loading CGRAM leaves the address counter pointing into it
+        self.command(LCD_SET_DDRAM_ADDR)
+    }
+
+    pub fn bus(&self) -> &B {
+        &self.bus
+    }
+
+    pub fn bus_mut(&mut self) -> &mut B {
+        &mut self.bus
+    }
+
+    pub fn release(self) -> B {
+        self.bus
+    }
+
+    // Swap the bus for one built from it, like a wrapper that watches what's sent. The new bus
+    // has to be the same width, the controller isn't set up again
+    pub fn map_bus<C: Bus, F: FnOnce(B) -> C>(self, f: F) -> Hd44780<C> {
+        Hd44780 {
+            bus: f(self.bus),
+            disp_func: self.disp_func,
+            disp_mode: self.disp_mode,
+            disp_control: self.disp_control,
+            num_cols: self.num_cols,
+            num_rows: self.num_rows,
+            row_offsets: self.row_offsets,
+        }
+    }
+
+    // Characters with no glyph in the ROM show up as `?`
+    pub fn print(&mut self, text: &str) -> Result<(), B::Error> {
+        for c in text.chars() {
+            self.write(encode(c).unwrap_or(b'?'))?;
+        }
+        Ok(())
+    }
+
+    pub fn display(&mut self) -> Result<(), B::Error> {
+        self.disp_control |= LCD_DISPLAY_ON;
+        self.command(LCD_DISPLAY_CONTROL | self.disp_control)
+    }
+
+    pub fn no_display(&mut self) -> Result<(), B::Error> {
+        self.disp_control &= !LCD_DISPLAY_ON;
+        self.command(LCD_DISPLAY_CONTROL | self.disp_control)
+    }
+
+    pub fn cursor(&mut self) -> Result<(), B::Error> {
+        self.disp_control |= LCD_CURSOR_ON;
+        self.command(LCD_DISPLAY_CONTROL | self.disp_control)
+    }
+
+    pub fn no_cursor(&mut self) -> Result<(), B::Error> {
+        self.disp_control &= !LCD_CURSOR_ON;
+        self.command(LCD_DISPLAY_CONTROL | self.disp_control)
+    }
+
+    pub fn blink(&mut self) -> Result<(), B::Error> {
+        self.disp_control |= LCD_BLINK_ON;
+        self.command(LCD_DISPLAY_CONTROL | self.disp_control)
+    }
+
+    pub fn no_blink(&mut self) -> Result<(), B::Error> {
+        self.disp_control &= !LCD_BLINK_ON;
+        self.command(LCD_DISPLAY_CONTROL | self.disp_control)
+    }
+
+    pub fn clear(&mut self) -> Result<(), B::Error> {
+        self.command(LCD_CLEAR_DISPLAY)?;
+        self.wait_long_command()
+    }
+
+    pub fn home(&mut self) -> Result<(), B::Error> {
+        self.command(LCD_RETURN_HOME)?;
+        self.wait_long_command()
+    }
+
+    // Waits for the controller to finish its last instruction by polling the busy flag. Returns
+    // false if the bus can't read it, or it stayed busy for too long, so the caller knows to fall
+    // back on a fixed delay
+    pub fn wait_busy(&mut self) -> Result<bool, B::Error> {
+        let mut waited = 0;
+        while waited < BUSY_TIMEOUT_US {
+            match self.bus.is_busy()? {
+                None => return Ok(false),
+                Some(false) => return Ok(true),
+                Some(true) => {
+                    self.bus.delay_us(BUSY_POLL_US);
+                    waited += BUSY_POLL_US;
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    fn wait_long_command(&mut self) -> Result<(), B::Error> {
+        if !self.wait_busy()? {
+            self.bus.delay_us(LONG_COMMAND_US);
+        }
+        Ok(())
+    }
+
+    pub fn set_cursor(&mut self, row: u8, col: u8) -> Result<(), B::Error> {
+        let row = row
+            .min(self.row_offsets.len() as u8 - 1)
+            .min(self.num_rows.saturating_sub(1));
+        self.command(LCD_SET_DDRAM_ADDR | (col + self.row_offsets[row as usize]))
+    }
+
+    pub fn create_char(&mut self, mut loc: u8, charmap: [u8; 8]) -> Result<(), B::Error> {
+        loc &= 0x07; // There are only 8 locations (0-7)
+        self.command(LCD_SET_CGRAM_ADDR | (loc << 3))?;
+        for row in charmap.iter() {
+            self.write(*row)?
+        }
+        Ok(())
+    }
+
+    pub fn command(&mut self, val: u8) -> Result<(), B::Error> {
+        self.send(val, false)
+    }
+
+    pub fn write(&mut self, val: u8) -> Result<(), B::Error> {
+        self.send(val, true)
+    }
+
+    pub fn get_rows(&self) -> u8 {
+        self.num_rows
+    }
+
+    pub fn get_cols(&self) -> u8 {
+        self.num_cols
+    }
+
+    fn send(&mut self, val: u8, data: bool) -> Result<(), B::Error> {
+        if self.bus.is_eight_bit() {
+            self.bus.write_bits(val, data)
+        } else {
+            self.bus.write_bits(val >> 4, data)?;
+            self.bus.write_bits(val & 0x0F, data)
+        }
+    }
+}
+
+// The character code for `c` in the standard (A00) character ROM. ASCII goes straight through,
+// which puts the CGRAM glyphs at `\u{0}` to `\u{7}`, but note the ROM has a yen sign in place of
+// `\` and arrows in place of `~` and DEL
+pub fn encode(c: char) -> Option<u8> {
+    if c.is_ascii() {
+        return Some(c as u8);
+    }
+    if let '｡'..='ﾟ' = c {
+        return Some((c as u32 - '｡' as u32) as u8 + 0xA1);
+    }
+    ROM_CHARS
+        .iter()
+        .find(|&&(rom_char, _)| rom_char == c)
+        .map(|&(_, code)| code)
+}
+
+// The other way around, `None` for the CGRAM codes and anything without an obvious character
+pub fn decode(code: u8) -> Option<char> {
+    match code {
+        0x20..=0x7D if code != b'\\' => Some(code as char),
+        _ => match ROM_CHARS.iter().find(|&&(_, rom_code)| rom_code == code) {
+            Some(&(rom_char, _)) => Some(rom_char),
+            // The half width katakana are in the same order as in Unicode
+            None if (0xA1..=0xDF).contains(&code) => {
+                core::char::from_u32('｡' as u32 + (code - 0xA1) as u32)
+            }
+            None => None,
+        },
+    }
+}
+
+// Where the ROM differs from ASCII, where a code has more than one character the first is what
+// it decodes to
+const ROM_CHARS: [(char, u8); 28] = [
+    ('¥', 0x5C),
+    ('→', 0x7E),
+    ('←', 0x7F),
+    ('°', 0xDF),
+    ('α', 0xE0),
+    ('ä', 0xE1),
+    ('β', 0xE2),
+    ('ß', 0xE2),
+    ('ε', 0xE3),
+    ('µ', 0xE4),
+    ('μ', 0xE4),
+    ('σ', 0xE5),
+    ('ρ', 0xE6),
+    ('√', 0xE8),
+    ('¢', 0xEC),
+    ('ñ', 0xEE),
+    ('ö', 0xEF),
+    ('θ', 0xF2),
+    ('∞', 0xF3),
+    ('Ω', 0xF4),
+    ('ü', 0xF5),
+    ('Σ', 0xF6),
+    ('π', 0xF7),
+    ('千', 0xFA),
+    ('万', 0xFB),
+    ('円', 0xFC),
+    ('÷', 0xFD),
+    ('█', 0xFF),
+];
+
+#[cfg(test)]
+mod test {
+    use crate::hd44780::*;
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+    use std::vec::Vec;
+
+    // Remembers every transfer instead of sending it anywhere
+    struct TestBus {
+        eight_bit: bool,
+        writes: Vec<(u8, bool)>,
+        // How many more reads of the busy flag come back busy, None for a bus that can't read
+        busy_reads: Option<u32>,
+        delayed_us: u32,
+    }
+
+    impl Bus for TestBus {
+        type Error = ();
+
+        fn is_eight_bit(&self) -> bool {
+            self.eight_bit
+        }
+
+        fn write_bits(&mut self, bits: u8, data: bool) -> Result<(), ()> {
+            self.writes.push((bits, data));
+            Ok(())
+        }
+
+        fn delay_us(&mut self, us: u32) {
+            self.delayed_us += us;
+        }
+
+        fn is_busy(&mut self) -> Result<Option<bool>, ()> {
+            Ok(self.busy_reads.map(|reads| {
+                self.busy_reads = Some(reads.saturating_sub(1));
+                reads > 0
+            }))
+        }
+    }
+
+    // Logs the lines it's set to under its name
+    struct TestPin {
+        name: &'static str,
+        log: Rc<RefCell<Vec<(&'static str, bool)>>>,
+    }
+
+    impl OutputPin for TestPin {
+        type Error = ();
+
+        fn set_low(&mut self) -> Result<(), ()> {
+            self.log.borrow_mut().push((self.name, false));
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), ()> {
+            self.log.borrow_mut().push((self.name, true));
+            Ok(())
+        }
+    }
+
+    struct TestInput(Rc<Cell<bool>>);
+
+    impl InputPin for TestInput {
+        type Error = ();
+
+        fn is_high(&self) -> Result<bool, ()> {
+            Ok(self.0.get())
+        }
+
+        fn is_low(&self) -> Result<bool, ()> {
+            Ok(!self.0.get())
+        }
+    }
+
+    struct NoDelay;
+
+    impl DelayUs<u32> for NoDelay {
+        fn delay_us(&mut self, _us: u32) {}
+    }
+
+    fn lcd(eight_bit: bool) -> Hd44780<TestBus> {
+        let bus = TestBus {
+            eight_bit,
+            writes: Vec::new(),
+            busy_reads: None,
+            delayed_us: 0,
+        };
+        Hd44780::new(bus, 16, 2)
+    }
+
+    fn pin_bus(log: &Rc<RefCell<Vec<(&'static str, bool)>>>, rw: bool) -> PinBus<TestPin, NoDelay> {
+        let pin = |name| TestPin {
+            name,
+            log: log.clone(),
+        };
+        let data = DataPins::Four([pin("d4"), pin("d5"), pin("d6"), pin("d7")]);
+        let rw = if rw { Some(pin("rw")) } else { None };
+        PinBus::new(pin("rs"), rw, pin("e"), data, NoDelay)
+    }
+
+    #[test]
+    fn init_test() {
+        let mut four_bit = lcd(false);
+        four_bit.init().unwrap();
+        let writes = &four_bit.bus().writes;
+        assert_eq!(
+            &writes[..6],
+            &[
+                (0x03, false),
+                (0x03, false),
+                (0x03, false),
+                (0x02, false),
+                // Function set for 4 bits and 2 lines, a nibble at a time
+                (0x02, false),
+                (0x08, false)
+            ]
+        );
+        let mut eight_bit = lcd(true);
+        eight_bit.init().unwrap();
+        assert_eq!(eight_bit.bus().writes[0], (0x38, false));
+    }
+
+    #[test]
+    fn print_test() {
+        let mut lcd = lcd(true);
+        lcd.set_cursor(5, 3).unwrap();
+        lcd.print("a°\u{1}é").unwrap();
+        assert_eq!(
+            lcd.bus().writes,
+            vec![
+                (LCD_SET_DDRAM_ADDR | 0x43, false),
+                (b'a', true),
+                (0xDF, true),
+                (0x01, true),
+                (b'?', true)
+            ]
+        );
+    }
+
+    #[test]
+    fn wait_busy_test() {
+        // Without a way to read, clear waits for as long as it could take
+        let mut lcd = lcd(false);
+        assert!(!lcd.wait_busy().unwrap());
+        lcd.clear().unwrap();
+        assert_eq!(lcd.bus().delayed_us, LONG_COMMAND_US);
+
+        let mut lcd = self::lcd(false);
+        lcd.bus_mut().busy_reads = Some(3);
+        lcd.clear().unwrap();
+        assert_eq!(lcd.bus().delayed_us, 3 * BUSY_POLL_US);
+        assert_eq!(lcd.bus().busy_reads, Some(0));
+
+        // A controller that never comes back is given up on
+        let mut lcd = self::lcd(false);
+        lcd.bus_mut().busy_reads = Some(u32::MAX);
+        assert!(!lcd.wait_busy().unwrap());
+        assert_eq!(lcd.bus().delayed_us, BUSY_TIMEOUT_US);
+        lcd.home().unwrap();
+        assert_eq!(lcd.bus().delayed_us, 2 * BUSY_TIMEOUT_US + LONG_COMMAND_US);
+    }
+
+    #[test]
+    fn pin_bus_busy_test() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let flag = Rc::new(Cell::new(true));
+        assert_eq!(pin_bus(&log, true).is_busy(), Ok(None));
+        let mut bus = pin_bus(&log, false).with_busy_pin(TestInput(flag.clone()));
+        assert_eq!(bus.is_busy(), Ok(None));
+        assert!(log.borrow().is_empty());
+
+        let mut bus = pin_bus(&log, true).with_busy_pin(TestInput(flag.clone()));
+        assert_eq!(bus.is_busy(), Ok(Some(true)));
+        // Both nibbles are clocked out and the data lines are released for the controller
+        assert_eq!(
+            *log.borrow(),
+            vec![
+                ("rs", false),
+                ("rw", true),
+                ("d4", true),
+                ("d5", true),
+                ("d6", true),
+                ("d7", true),
+                ("e", true),
+                ("e", false),
+                ("e", true),
+                ("e", false),
+                ("rw", false)
+            ]
+        );
+        flag.set(false);
+        assert_eq!(bus.is_busy(), Ok(Some(false)));
+        let (_, input) = bus.release_busy_pin();
+        assert!(!input.0.get());
+    }
+}
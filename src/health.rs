@@ -0,0 +1,180 @@
+use gpio_cdev::errors::Error;
+use std::time::{Duration, Instant};
+
+// What the worker does when writing to the display fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    // Wait and try again, the wait doubles with every error in a row up to `max`
+    Retry { initial: Duration, max: Duration },
+    // Wait the same as `Retry`, then run the controller's init sequence again before carrying on.
+    // For displays that get into a bad state after a glitch on the bus
+    Reinitialize { initial: Duration, max: Duration },
+    // Stop the worker, the display is left showing whatever it last got
+    Stop,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkerState {
+    Running,
+    // The last write failed and the worker is backing off
+    Recovering,
+    // Stopped by the error policy or by shutting down
+    Stopped,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Health {
+    pub state: WorkerState,
+    // Every error since the worker started
+    pub errors: u64,
+    // Errors since the last write that went through
+    pub consecutive_errors: u32,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<Instant>,
+    pub last_ok_at: Option<Instant>,
+}
+
+// What the worker should do about an error it just reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Recovery {
+    Retry(Duration),
+    Reinitialize(Duration),
+    Stop,
+}
+
+impl Default for ErrorPolicy {
+    fn default() -> Self {
+        ErrorPolicy::Retry {
+            initial: Duration::from_millis(100),
+            max: Duration::from_secs(10),
+        }
+    }
+}
+
+impl ErrorPolicy {
+    // How long to wait after the given number of errors in a row
+    pub fn backoff(&self, consecutive_errors: u32) -> Duration {
+        match *self {
+            ErrorPolicy::Retry { initial, max } | ErrorPolicy::Reinitialize { initial, max } => {
+                let doublings = consecutive_errors.saturating_sub(1).min(31);
+                initial
+                    .checked_mul(1 << doublings)
+                    .map_or(max, |backoff| backoff.min(max))
+            }
+            ErrorPolicy::Stop => Duration::from_secs(0),
+        }
+    }
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Health::new()
+    }
+}
+
+impl Health {
+    pub fn new() -> Self {
+        Health {
+            state: WorkerState::Running,
+            errors: 0,
+            consecutive_errors: 0,
+            last_error: None,
+            last_error_at: None,
+            last_ok_at: None,
+        }
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.state == WorkerState::Running
+    }
+
+    pub(crate) fn record_ok(&mut self, now: Instant) {
+        if self.state == WorkerState::Recovering {
+            self.state = WorkerState::Running;
+        }
+        self.consecutive_errors = 0;
+        self.last_ok_at = Some(now);
+    }
+
+    pub(crate) fn record_error(
+        &mut self,
+        error: &Error,
+        policy: ErrorPolicy,
+        now: Instant,
+    ) -> Recovery {
+        self.errors += 1;
+        self.consecutive_errors = self.consecutive_errors.saturating_add(1);
+        self.last_error = Some(error.to_string());
+        self.last_error_at = Some(now);
+        let backoff = policy.backoff(self.consecutive_errors);
+        match policy {
+            ErrorPolicy::Retry { .. } => {
+                self.state = WorkerState::Recovering;
+                Recovery::Retry(backoff)
+            }
+            ErrorPolicy::Reinitialize { .. } => {
+                self.state = WorkerState::Recovering;
+                Recovery::Reinitialize(backoff)
+            }
+            ErrorPolicy::Stop => {
+                self.state = WorkerState::Stopped;
+                Recovery::Stop
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::health::*;
+    use std::io::ErrorKind;
+
+    fn io_error() -> Error {
+        Error::from(std::io::Error::new(ErrorKind::BrokenPipe, "bus error"))
+    }
+
+    #[test]
+    fn backoff_test() {
+        let policy = ErrorPolicy::Retry {
+            initial: Duration::from_millis(100),
+            max: Duration::from_millis(500),
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(4), Duration::from_millis(500));
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn health_test() {
+        let now = Instant::now();
+        let mut health = Health::new();
+        let policy = ErrorPolicy::Reinitialize {
+            initial: Duration::from_millis(10),
+            max: Duration::from_secs(1),
+        };
+        assert_eq!(
+            health.record_error(&io_error(), policy, now),
+            Recovery::Reinitialize(Duration::from_millis(10))
+        );
+        assert_eq!(
+            health.record_error(&io_error(), policy, now),
+            Recovery::Reinitialize(Duration::from_millis(20))
+        );
+        assert_eq!(health.state, WorkerState::Recovering);
+        assert_eq!(health.consecutive_errors, 2);
+        assert!(health.last_error.is_some());
+        health.record_ok(now);
+        assert!(health.is_healthy());
+        assert_eq!((health.errors, health.consecutive_errors), (2, 0));
+        assert_eq!(
+            health.record_error(&io_error(), ErrorPolicy::Stop, now),
+            Recovery::Stop
+        );
+        assert_eq!(health.state, WorkerState::Stopped);
+        // Nothing brings a stopped worker back
+        health.record_ok(now);
+        assert_eq!(health.state, WorkerState::Stopped);
+    }
+}
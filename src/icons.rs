@@ -1,3 +1,4 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Icon {
     MAIL,
     BELL,
@@ -32,4 +33,9 @@ impl Icon {
             Icon::PAUSE => 6,
         }
     }
+
+    // The character code that displays this icon once it has been loaded into CGRAM
+    pub fn as_char(&self) -> char {
+        self.index() as char
+    }
 }
@@ -1,68 +1,49 @@
-use std::thread::{sleep, JoinHandle};
-use std::time::{Duration, Instant, SystemTime, SystemTimeError};
-
-use crate::icons::Icon;
+use std::cell::{Cell, RefCell};
+use std::convert::TryInto;
+use std::error::Error as StdError;
+use std::fmt;
+use std::thread::sleep;
+use std::time::Duration;
+
+use crate::font::Rom;
+use crate::hd44780::{Bus, DataPins, Hd44780, PinBus};
+use embedded_hal::blocking::delay::DelayUs;
+use embedded_hal::digital::v2::OutputPin;
 use gpio_cdev::errors::Error;
 use gpio_cdev::*;
-use parking_lot::Mutex;
-use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashMap};
-use std::ops::DerefMut;
-use std::sync::{mpsc, Arc};
-use std::thread;
-use unidecode::unidecode;
+use unidecode::{unidecode, unidecode_char};
 // TODO add independent row scrolling and custom characters
 
-// Adapted from Arduino standard library LiquidCrystal.cpp/h
-
-// Commands
-const LCD_CLEAR_DISPLAY: u8 = 0x01;
-const LCD_RETURN_HOME: u8 = 0x02;
-const LCD_ENTRY_MODE_SET: u8 = 0x04;
-const LCD_DISPLAY_CONTROL: u8 = 0x08;
-const LCD_CURSOR_SHIFT: u8 = 0x10;
-const LCD_FUNCTION_SET: u8 = 0x20;
-const LCD_SET_CGRAM_ADDR: u8 = 0x40;
-const LCD_SET_DDRAM_ADDR: u8 = 0x80;
-
-// Display entry mode
-const LCD_ENTRY_LEFT: u8 = 0x02;
-const LCD_ENTRY_SHIFT_DECREMENT: u8 = 0x00;
-
-// Display on/off control
-const LCD_DISPLAY_ON: u8 = 0x04;
-const LCD_CURSOR_OFF: u8 = 0x00;
-const LCD_BLINK_OFF: u8 = 0x00;
-
-// Display/cursor shift
-const LCD_LEFT: u8 = 0x00;
-const LCD_CURSOR_MOVE: u8 = 0x00;
-const LCD_RIGHT: u8 = 0x04;
-const LCD_DISPLAY_MOVE: u8 = 0x08;
-
-// Function setting
-const LCD_4BITMODE: u8 = 0x00;
-const LCD_8BITMODE: u8 = 0x10;
-const LCD_1LINE: u8 = 0x00;
-const LCD_2LINE: u8 = 0x08;
-const LCD_5X8DOTS: u8 = 0x00;
-
-#[derive(Debug)]
+// Anything the driver can send commands through
+pub type BoxedBus = Box<dyn Bus<Error = Error> + Send>;
+
+type BoxedPin = Box<dyn OutputPin<Error = Error> + Send>;
+
 pub struct LcdDriver {
-    chip: Chip,
-    rs_line: LineHandle,
-    rw_line: Option<LineHandle>,
-    enable_line: LineHandle,
-    data_lines: Vec<Option<LineHandle>>,
-    disp_func: u8,
-    disp_mode: u8,
-    disp_control: u8,
-    num_cols: u8,
-    num_rows: u8,
-    row_offsets: [u8; 4],
+    // Behind a RefCell so drawing only needs a shared reference, the driver is always used from
+    // one thread at a time
+    lcd: RefCell<Hd44780<BoxedBus>>,
+    // Only there when the driver opened the GPIO chip itself
+    chip: Option<Chip>,
+    backlight: Option<RefCell<BoxedPin>>,
+    // Decides the codes `print` sends
+    rom: Rom,
+    // Slots written with `create_char`, put back after the icons whenever `init` runs
+    glyphs: Cell<[Option<[u8; 8]>; 8]>,
 }
 
+// A requested cdev line as an embedded_hal pin
+pub struct LinePin(LineHandle);
+
+// Wraps the errors of any embedded_hal pin as the driver's error type
+struct AnyPin<P>(P);
+
+// Delays by sleeping the thread, which is close enough for the controller's timings
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SleepDelay;
+
 impl LcdDriver {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         cols: u8,
         rows: u8,
@@ -81,128 +62,295 @@ impl LcdDriver {
         d7: u8,
     ) -> Result<Self, gpio_cdev::errors::Error> {
         let mut chip = Chip::new(chip_str)?;
-        let rs_line = chip
-            .get_line(rs as u32)?
-            .request(LineRequestFlags::OUTPUT, 0, "lcd")?;
+        let mut lcd_struct = LcdDriver::from_lines(
+            cols,
+            rows,
+            four_bit_mode,
+            rs,
+            rw,
+            enable,
+            [d0, d1, d2, d3, d4, d5, d6, d7],
+            true,
+            |line| LinePin::request(&mut chip, line),
+        )?;
+        lcd_struct.chip = Some(chip);
+        Ok(lcd_struct)
+    }
+
+    // Like `new` for a display that was set up by an earlier run, what's on it is left alone. The
+    // controller has to be in the mode given, which it is if it was set up by this driver
+    #[allow(clippy::too_many_arguments)]
+    pub fn attach(
+        cols: u8,
+        rows: u8,
+        chip_str: &str,
+        four_bit_mode: bool,
+        rs: u8,
+        rw: u8,
+        enable: u8,
+        d0: u8,
+        d1: u8,
+        d2: u8,
+        d3: u8,
+        d4: u8,
+        d5: u8,
+        d6: u8,
+        d7: u8,
+    ) -> Result<Self, Error> {
+        let mut chip = Chip::new(chip_str)?;
+        let mut lcd_struct = LcdDriver::from_lines(
+            cols,
+            rows,
+            four_bit_mode,
+            rs,
+            rw,
+            enable,
+            [d0, d1, d2, d3, d4, d5, d6, d7],
+            false,
+            |line| LinePin::request(&mut chip, line),
+        )?;
+        lcd_struct.chip = Some(chip);
+        Ok(lcd_struct)
+    }
+
+    // The same as `new` for boards that only have the older sysfs GPIO interface
+    #[cfg(feature = "linux-embedded-hal")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn sysfs(
+        cols: u8,
+        rows: u8,
+        four_bit_mode: bool,
+        rs: u8,
+        rw: u8,
+        enable: u8,
+        d0: u8,
+        d1: u8,
+        d2: u8,
+        d3: u8,
+        d4: u8,
+        d5: u8,
+        d6: u8,
+        d7: u8,
+    ) -> Result<Self, Error> {
+        use linux_embedded_hal::sysfs_gpio::Direction;
+        use linux_embedded_hal::SysfsPin;
+
+        LcdDriver::from_lines(
+            cols,
+            rows,
+            four_bit_mode,
+            rs,
+            rw,
+            enable,
+            [d0, d1, d2, d3, d4, d5, d6, d7],
+            true,
+            |line| {
+                let pin = SysfsPin::new(line as u64);
+                pin.export().map_err(pin_error)?;
+                pin.set_direction(Direction::Low).map_err(pin_error)?;
+                Ok(pin)
+            },
+        )
+    }
 
-        let rw_line = match rw {
+    // Drive the display from any embedded_hal pins, like linux-embedded-hal's `CdevPin` or
+    // `SysfsPin`. The pins are expected to be set up as outputs already
+    pub fn with_pins<P, D>(
+        cols: u8,
+        rows: u8,
+        rs: P,
+        rw: Option<P>,
+        enable: P,
+        data_pins: DataPins<P>,
+        delay: D,
+    ) -> Result<Self, Error>
+    where
+        P: OutputPin + Send + 'static,
+        P::Error: StdError + Send + Sync + 'static,
+        D: DelayUs<u32> + Send + 'static,
+    {
+        let bus = PinBus::new(
+            AnyPin(rs),
+            rw.map(AnyPin),
+            AnyPin(enable),
+            data_pins.map(AnyPin),
+            delay,
+        );
+        LcdDriver::from_bus(bus, cols, rows)
+    }
+
+    // Shared by the constructors that take line numbers, 255 marks a line that isn't connected.
+    // In 4 bit mode the first four data lines given are used
+    #[allow(clippy::too_many_arguments)]
+    fn from_lines<P, F>(
+        cols: u8,
+        rows: u8,
+        four_bit_mode: bool,
+        rs: u8,
+        rw: u8,
+        enable: u8,
+        data_lines: [u8; 8],
+        init: bool,
+        mut request: F,
+    ) -> Result<Self, Error>
+    where
+        P: OutputPin + Send + 'static,
+        P::Error: StdError + Send + Sync + 'static,
+        F: FnMut(u8) -> Result<P, Error>,
+    {
+        let rs = request(rs)?;
+        let rw = match rw {
             255 => None,
-            _ => Some(
-                chip.get_line(rw as u32)?
-                    .request(LineRequestFlags::OUTPUT, 0, "lcd")
-                    .unwrap(),
-            ),
+            _ => Some(request(rw)?),
         };
+        let enable = request(enable)?;
 
-        let mut none_count = 0;
-        let mut data_lines: Vec<Option<LineHandle>> = [d0, d1, d2, d3, d4, d5, d6, d7]
-            .iter()
-            .map(|line_num| match line_num {
-                255 => {
-                    none_count += 1;
-                    None
-                }
-                _ => Some(
-                    chip.get_line(*line_num as u32)
-                        .unwrap()
-                        .request(LineRequestFlags::OUTPUT, 0, "lcd")
-                        .unwrap(),
-                ),
-            })
-            .filter(|x| x.is_some())
-            .collect();
-        for _i in 0..none_count {
-            data_lines.push(None);
+        let mut data_pins = Vec::new();
+        for line in data_lines.iter() {
+            if *line != 255 {
+                data_pins.push(request(*line)?);
+            }
         }
-
-        if none_count != 0 && none_count != 4 {
-            return Err(errors::Error::from(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "Wrong number of unused pins",
-            )));
+        let data_pins = match (four_bit_mode, data_pins.len()) {
+            (true, 4) | (true, 8) => {
+                data_pins.truncate(4);
+                DataPins::Four(data_pins.try_into().ok().unwrap())
+            }
+            (false, 8) => DataPins::Eight(data_pins.try_into().ok().unwrap()),
+            _ => {
+                return Err(errors::Error::from(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "Wrong number of unused pins",
+                )))
+            }
+        };
+        if init {
+            LcdDriver::with_pins(cols, rows, rs, rw, enable, data_pins, SleepDelay)
+        } else {
+            let bus = PinBus::new(
+                AnyPin(rs),
+                rw.map(AnyPin),
+                AnyPin(enable),
+                data_pins.map(AnyPin),
+                SleepDelay,
+            );
+            Ok(LcdDriver::attach_bus(bus, cols, rows))
         }
+    }
 
-        let mut disp_func = if four_bit_mode {
-            LCD_4BITMODE | LCD_1LINE | LCD_5X8DOTS
-        } else {
-            LCD_8BITMODE | LCD_1LINE | LCD_5X8DOTS
-        };
+    // Drive the display through any bus, the controller is initialised straight away
+    pub fn from_bus<B>(bus: B, cols: u8, rows: u8) -> Result<Self, Error>
+    where
+        B: Bus<Error = Error> + Send + 'static,
+    {
+        let mut lcd = LcdDriver::attach_bus(bus, cols, rows);
+        lcd.init()?;
+        Ok(lcd)
+    }
 
-        if cols > 1 {
-            disp_func |= LCD_2LINE;
+    // Drive a display through a bus without initialising it, for when that's already been done
+    pub fn attach_bus<B>(bus: B, cols: u8, rows: u8) -> Self
+    where
+        B: Bus<Error = Error> + Send + 'static,
+    {
+        LcdDriver {
+            lcd: RefCell::new(Hd44780::new(Box::new(bus) as BoxedBus, cols, rows)),
+            chip: None,
+            backlight: None,
+            rom: Rom::A00,
+            glyphs: Cell::new([None; 8]),
         }
-        let row_offsets = [0x00, 0x40, 0x00 + cols, 0x40 + cols];
-
-        let enable_line =
-            chip.get_line(enable as u32)?
-                .request(LineRequestFlags::OUTPUT, 0, "lcd")?;
+    }
 
-        let disp_control = LCD_DISPLAY_ON | LCD_CURSOR_OFF | LCD_BLINK_OFF;
-        let disp_mode = LCD_ENTRY_LEFT | LCD_ENTRY_SHIFT_DECREMENT;
+    // Put something between the driver and its bus, like a `TraceBus` or `RecordBus`. Works on
+    // a driver that's already running, the display is left as it is
+    pub fn map_bus<B, F>(self, f: F) -> Self
+    where
+        B: Bus<Error = Error> + Send + 'static,
+        F: FnOnce(BoxedBus) -> B,
+    {
+        let lcd = self
+            .lcd
+            .into_inner()
+            .map_bus(|bus| Box::new(f(bus)) as BoxedBus);
+        LcdDriver {
+            lcd: RefCell::new(lcd),
+            ..self
+        }
+    }
 
-        rs_line.set_value(0)?;
-        enable_line.set_value(0)?;
-        if rw_line.is_some() {
-            rw_line.as_ref().unwrap().set_value(0)?;
+    // Runs the controller's power on sequence, which also clears the display and reloads the
+    // icons into CGRAM, followed by any characters created since. Safe to call again on a display
+    // that has gotten out of sync
+    pub fn init(&mut self) -> Result<(), Error> {
+        let lcd = self.lcd.get_mut();
+        lcd.init()?;
+        let glyphs = self.glyphs.get();
+        if glyphs.iter().any(Option::is_some) {
+            for (loc, charmap) in glyphs.iter().enumerate() {
+                if let Some(charmap) = charmap {
+                    lcd.create_char(loc as u8, *charmap)?;
+                }
+            }
+            lcd.set_cursor(0, 0)?;
         }
+        Ok(())
+    }
 
-        let mut lcd_struct = LcdDriver {
-            chip,
-            rs_line,
-            rw_line,
-            enable_line,
-            data_lines,
-            disp_func,
-            disp_control,
-            disp_mode,
-            num_cols: cols,
-            num_rows: rows,
-            row_offsets,
+    // For boards that switch the backlight from a GPIO line, it starts out on
+    pub fn with_backlight(mut self, backlight: u8) -> Result<Self, Error> {
+        let chip = match self.chip.as_mut() {
+            Some(chip) => chip,
+            None => {
+                return Err(Error::from(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "No GPIO chip to request the backlight line from",
+                )))
+            }
         };
+        let backlight_line =
+            chip.get_line(backlight as u32)?
+                .request(LineRequestFlags::OUTPUT, 1, "lcd")?;
+        Ok(self.with_backlight_pin(LinePin(backlight_line)))
+    }
 
-        if (disp_func & LCD_8BITMODE) == 0 {
-            lcd_struct.write4bits(0x03)?;
-            sleep(Duration::from_micros(4500));
-
-            lcd_struct.write4bits(0x03)?;
-            sleep(Duration::from_micros(4500));
-
-            lcd_struct.write4bits(0x03)?;
-            sleep(Duration::from_micros(150));
-
-            lcd_struct.write4bits(0x02)?;
-        } else {
-            lcd_struct.command(LCD_FUNCTION_SET | disp_func)?;
-            sleep(Duration::from_micros(4500));
+    // For displays with a character ROM other than the standard A00
+    pub fn with_rom(mut self, rom: Rom) -> Self {
+        self.rom = rom;
+        self
+    }
 
-            lcd_struct.command(LCD_FUNCTION_SET | disp_func)?;
-            sleep(Duration::from_micros(150));
+    // The pin is left as it is, so it should already be turned on
+    pub fn with_backlight_pin<P>(mut self, backlight: P) -> Self
+    where
+        P: OutputPin + Send + 'static,
+        P::Error: StdError + Send + Sync + 'static,
+    {
+        self.backlight = Some(RefCell::new(Box::new(AnyPin(backlight))));
+        self
+    }
 
-            lcd_struct.command(LCD_FUNCTION_SET | disp_func)?;
+    // Does nothing if there is no backlight pin
+    pub fn backlight(&self, on: bool) -> Result<(), Error> {
+        if let Some(backlight) = self.backlight.as_ref() {
+            let mut backlight = backlight.borrow_mut();
+            if on {
+                backlight.set_high()?;
+            } else {
+                backlight.set_low()?;
+            }
         }
-
-        lcd_struct.command(LCD_FUNCTION_SET | disp_func)?;
-        lcd_struct.display()?;
-        lcd_struct.clear()?;
-
-        lcd_struct.command(LCD_ENTRY_MODE_SET | disp_mode)?;
-
-        lcd_struct.create_char(Icon::MAIL.index(), Icon::MAIL.char_data())?;
-        lcd_struct.create_char(Icon::BELL.index(), Icon::BELL.char_data())?;
-        lcd_struct.create_char(Icon::FILLEDBOX.index(), Icon::FILLEDBOX.char_data())?;
-        lcd_struct.create_char(Icon::EMPTYBOX.index(), Icon::EMPTYBOX.char_data())?;
-        lcd_struct.create_char(Icon::MAIL.index(), Icon::MAIL.char_data())?;
-        lcd_struct.create_char(Icon::BELL.index(), Icon::BELL.char_data())?;
-        lcd_struct.create_char(Icon::MUSIC.index(), Icon::MUSIC.char_data())?;
-        lcd_struct.create_char(Icon::PLAY.index(), Icon::PLAY.char_data())?;
-        lcd_struct.create_char(Icon::PAUSE.index(), Icon::PAUSE.char_data())?;
-
-        Ok(lcd_struct)
+        Ok(())
     }
 
+    // Characters the ROM doesn't have are transliterated to ASCII
     pub fn print(&self, disp_str: &str) -> Result<(), Error> {
-        for c in unidecode(disp_str).bytes() {
-            self.write(c)?
+        let mut lcd = self.lcd.borrow_mut();
+        for c in disp_str.chars() {
+            match self.rom.encode(c) {
+                Some(code) => lcd.write(code)?,
+                None => lcd.print(unidecode_char(c))?,
+            }
         }
         Ok(())
     }
@@ -211,7 +359,7 @@ impl LcdDriver {
         self.set_cursor(0, 0)?;
         let mut char_count = 0;
         for c in unidecode(disp_str).bytes() {
-            self.write(c as u8)?;
+            self.write(c)?;
             char_count += 1;
             if char_count == 16 {
                 self.set_cursor(1, 0)?;
@@ -221,111 +369,140 @@ impl LcdDriver {
     }
 
     pub fn display(&mut self) -> Result<(), Error> {
-        self.disp_control &= LCD_DISPLAY_ON;
-        self.command(LCD_DISPLAY_CONTROL | self.disp_control)?;
-        Ok(())
+        self.lcd.get_mut().display()
     }
 
     pub fn no_display(&mut self) -> Result<(), Error> {
-        self.disp_control &= !LCD_DISPLAY_ON;
-        self.command(LCD_DISPLAY_CONTROL | self.disp_control)?;
-        Ok(())
+        self.lcd.get_mut().no_display()
     }
 
-    pub fn clear(&self) -> Result<(), Error> {
-        self.command(LCD_CLEAR_DISPLAY)?;
-        sleep(Duration::from_micros(2000));
-        Ok(())
+    pub fn cursor(&mut self) -> Result<(), Error> {
+        self.lcd.get_mut().cursor()
     }
 
-    pub fn home(&self) -> Result<(), Error> {
-        self.command(LCD_RETURN_HOME)?;
-        sleep(Duration::from_micros(2000));
-        Ok(())
+    pub fn no_cursor(&mut self) -> Result<(), Error> {
+        self.lcd.get_mut().no_cursor()
     }
 
-    pub fn set_cursor(&self, mut row: u8, col: u8) -> Result<(), Error> {
-        let max_lines = self.row_offsets.len() as u8;
-        if row >= max_lines {
-            row = max_lines - 1;
-        }
-        if row >= self.num_cols {
-            row = self.num_cols - 1;
-        }
-        self.command(LCD_SET_DDRAM_ADDR | (col + self.row_offsets[row as usize]))
+    pub fn blink(&mut self) -> Result<(), Error> {
+        self.lcd.get_mut().blink()
     }
 
-    fn send(&self, val: u8, mode: u8) -> Result<(), errors::Error> {
-        self.rs_line.set_value(mode)?;
-        if self.rw_line.is_some() {
-            self.rw_line.as_ref().unwrap().set_value(0)?;
-        }
-        if (self.disp_func & LCD_8BITMODE) != 0 {
-            self.write8bits(val)?;
-        } else {
-            self.write4bits(val >> 4)?;
-            self.write4bits(val)?;
-        }
-
-        Ok(())
+    pub fn no_blink(&mut self) -> Result<(), Error> {
+        self.lcd.get_mut().no_blink()
     }
 
-    fn pulse_enable(&self) -> Result<(), errors::Error> {
-        self.enable_line.set_value(0)?;
-        sleep(Duration::from_micros(10));
-        self.enable_line.set_value(1)?;
-        sleep(Duration::from_micros(10));
-        self.enable_line.set_value(0)?;
-        sleep(Duration::from_micros(100));
-        Ok(())
+    pub fn clear(&self) -> Result<(), Error> {
+        self.lcd.borrow_mut().clear()
     }
 
-    fn write4bits(&self, val: u8) -> Result<(), errors::Error> {
-        for i in 0..4 {
-            self.data_lines[i]
-                .as_ref()
-                .unwrap()
-                .set_value((val >> i as u8) & 0x01)?;
-        }
-        self.pulse_enable()?;
-        Ok(())
+    pub fn home(&self) -> Result<(), Error> {
+        self.lcd.borrow_mut().home()
     }
 
-    fn write8bits(&self, val: u8) -> Result<(), errors::Error> {
-        for i in 0..8 {
-            if self.data_lines[i].is_some() {
-                self.data_lines[i]
-                    .as_ref()
-                    .unwrap()
-                    .set_value((val >> i as u8) & 0x01)?;
-            }
-        }
-        self.pulse_enable()?;
-        Ok(())
+    pub fn set_cursor(&self, row: u8, col: u8) -> Result<(), Error> {
+        self.lcd.borrow_mut().set_cursor(row, col)
     }
 
     pub fn command(&self, val: u8) -> Result<(), Error> {
-        self.send(val, 0)
+        self.lcd.borrow_mut().command(val)
     }
 
     pub fn write(&self, val: u8) -> Result<(), Error> {
-        self.send(val, 1)
+        self.lcd.borrow_mut().write(val)
     }
 
-    pub fn create_char(&self, mut loc: u8, charmap: [u8; 8]) -> Result<(), Error> {
-        loc &= 0x07; // There are only 8 locations (0-7)
-        self.command(LCD_SET_CGRAM_ADDR | (loc << 3))?;
-        for i in 0..8 {
-            self.write(charmap[i])?
-        }
-        Ok(())
+    pub fn create_char(&self, loc: u8, charmap: [u8; 8]) -> Result<(), Error> {
+        let mut glyphs = self.glyphs.get();
+        glyphs[(loc & 0x07) as usize] = Some(charmap);
+        self.glyphs.set(glyphs);
+        self.lcd.borrow_mut().create_char(loc, charmap)
     }
 
     pub fn get_rows(&self) -> u8 {
-        self.num_rows
+        self.lcd.borrow().get_rows()
     }
 
     pub fn get_cols(&self) -> u8 {
-        self.num_cols
+        self.lcd.borrow().get_cols()
+    }
+}
+
+impl fmt::Debug for LcdDriver {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LcdDriver")
+            .field("cols", &self.get_cols())
+            .field("rows", &self.get_rows())
+            .field("chip", &self.chip)
+            .field("backlight", &self.backlight.is_some())
+            .field("rom", &self.rom)
+            .finish()
+    }
+}
+
+impl<B: Bus + ?Sized> Bus for Box<B> {
+    type Error = B::Error;
+
+    fn is_eight_bit(&self) -> bool {
+        (**self).is_eight_bit()
+    }
+
+    fn write_bits(&mut self, bits: u8, data: bool) -> Result<(), B::Error> {
+        (**self).write_bits(bits, data)
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        (**self).delay_us(us)
+    }
+
+    fn is_busy(&mut self) -> Result<Option<bool>, B::Error> {
+        (**self).is_busy()
+    }
+}
+
+impl LinePin {
+    pub fn request(chip: &mut Chip, line: u8) -> Result<Self, Error> {
+        let handle = chip
+            .get_line(line as u32)?
+            .request(LineRequestFlags::OUTPUT, 0, "lcd")?;
+        Ok(LinePin(handle))
+    }
+}
+
+impl OutputPin for LinePin {
+    type Error = Error;
+
+    fn set_low(&mut self) -> Result<(), Error> {
+        self.0.set_value(0)
+    }
+
+    fn set_high(&mut self) -> Result<(), Error> {
+        self.0.set_value(1)
+    }
+}
+
+impl<P> OutputPin for AnyPin<P>
+where
+    P: OutputPin,
+    P::Error: StdError + Send + Sync + 'static,
+{
+    type Error = Error;
+
+    fn set_low(&mut self) -> Result<(), Error> {
+        self.0.set_low().map_err(pin_error)
+    }
+
+    fn set_high(&mut self) -> Result<(), Error> {
+        self.0.set_high().map_err(pin_error)
+    }
+}
+
+fn pin_error<E: StdError + Send + Sync + 'static>(error: E) -> Error {
+    Error::from(std::io::Error::other(error))
+}
+
+impl DelayUs<u32> for SleepDelay {
+    fn delay_us(&mut self, us: u32) {
+        sleep(Duration::from_micros(us as u64));
     }
 }
@@ -1,3 +1,47 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(feature = "tokio")]
+pub mod async_lcd;
+#[cfg(feature = "std")]
+pub mod big_font;
+#[cfg(feature = "std")]
+pub mod canvas;
+#[cfg(feature = "std")]
+pub mod clock;
+#[cfg(feature = "std")]
+pub mod config;
+#[cfg(feature = "daemon")]
+pub mod daemon;
+pub mod font;
+pub mod hd44780;
+#[cfg(feature = "std")]
+pub mod health;
 pub mod icons;
+#[cfg(feature = "std")]
 pub mod lcd;
+#[cfg(feature = "std")]
+pub mod notify;
+#[cfg(feature = "std")]
+pub mod pages;
+#[cfg(feature = "std")]
+pub mod pipe;
+#[cfg(feature = "std")]
+pub mod region;
+#[cfg(feature = "std")]
+pub mod replay;
+#[cfg(feature = "std")]
+pub mod schedule;
+#[cfg(feature = "std")]
 pub mod scheduler;
+#[cfg(feature = "std")]
+pub mod scroll;
+#[cfg(feature = "std")]
+pub mod sim;
+#[cfg(feature = "std")]
+pub mod snapshot;
+#[cfg(feature = "std")]
+pub mod terminal;
+#[cfg(feature = "std")]
+pub mod trace;
+#[cfg(feature = "std")]
+pub mod widgets;
@@ -0,0 +1,146 @@
+use crate::icons::Icon;
+use std::time::Duration;
+use unidecode::unidecode;
+
+pub type NotificationId = u64;
+
+// An alert that takes over the whole display for a while, jobs are suspended underneath it and
+// pick up where they left off once it is gone
+#[derive(Debug, Clone)]
+pub struct Notification {
+    text: String,
+    duration: Duration,
+    // Higher priorities are shown first, equal priorities in the order they were sent
+    priority: u8,
+    // Time the text is shown for and then hidden for
+    blink: Option<Duration>,
+    icon: Option<Icon>,
+}
+
+impl Notification {
+    pub fn new(text: &str, duration: Duration) -> Self {
+        Notification {
+            text: unidecode(text),
+            duration,
+            priority: 0,
+            blink: None,
+            icon: None,
+        }
+    }
+
+    pub fn with_priority(mut self, priority: u8) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_blink(mut self, interval: Duration) -> Self {
+        self.blink = Some(interval);
+        self
+    }
+
+    // The icon has to be in CGRAM, which it is unless something else has been loaded over it
+    pub fn with_icon(mut self, icon: Icon) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    pub fn priority(&self) -> u8 {
+        self.priority
+    }
+
+    pub fn blink(&self) -> Option<Duration> {
+        self.blink
+    }
+
+    // The text is word wrapped and centered, anything that doesn't fit is cut off
+    pub fn screen(&self, cols: u8, rows: u8, visible: bool) -> Vec<String> {
+        let (cols, rows) = (cols as usize, rows as usize);
+        let blank = " ".repeat(cols);
+        if !visible {
+            return vec![blank; rows];
+        }
+        let text = match self.icon {
+            Some(icon) => format!("{} {}", icon.as_char(), self.text),
+            None => self.text.clone(),
+        };
+        let lines = wrap(&text, cols);
+        let top = rows.saturating_sub(lines.len()) / 2;
+        (0..rows)
+            .map(
+                |row| match row.checked_sub(top).and_then(|i| lines.get(i)) {
+                    Some(line) => {
+                        let left = (cols - line.len()) / 2;
+                        format!("{}{: <width$}", &blank[..left], line, width = cols - left)
+                    }
+                    None => blank.clone(),
+                },
+            )
+            .collect()
+    }
+}
+
+// Break on spaces and newlines, words longer than a line are split
+fn wrap(text: &str, cols: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    if cols == 0 {
+        return lines;
+    }
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split(' ').filter(|word| !word.is_empty()) {
+            if !line.is_empty() && line.len() + 1 + word.len() > cols {
+                lines.push(line);
+                line = String::new();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+            while line.len() > cols {
+                let rest = line.split_off(cols);
+                lines.push(line);
+                line = rest;
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod test {
+    use crate::notify::*;
+
+    #[test]
+    fn wrap_test() {
+        assert_eq!(wrap("Door open!", 16), vec!["Door open!"]);
+        assert_eq!(wrap("Back door is open", 10), vec!["Back door", "is open"]);
+        assert_eq!(wrap("Line one\nTwo", 16), vec!["Line one", "Two"]);
+        assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+    }
+
+    #[test]
+    fn screen_test() {
+        let notification = Notification::new("Door open!", Duration::from_secs(5));
+        assert_eq!(
+            notification.screen(16, 2, true),
+            vec!["   Door open!   ", "                "]
+        );
+        assert_eq!(
+            notification.screen(16, 4, true),
+            vec![
+                "                ",
+                "   Door open!   ",
+                "                ",
+                "                "
+            ]
+        );
+        assert_eq!(notification.screen(4, 1, false), vec!["    "]);
+        let notification = notification.with_icon(Icon::BELL);
+        assert_eq!(notification.screen(14, 1, true)[0], " \u{1} Door open! ");
+    }
+}
@@ -0,0 +1,163 @@
+use crate::lcd::LcdDriver;
+use crate::scheduler::Job;
+use gpio_cdev::errors::Error;
+use std::thread::sleep;
+use std::time::Duration;
+
+pub type PageId = u64;
+
+// A screen's worth of jobs, only the jobs of the page being shown get to run
+#[derive(Debug, Default)]
+pub struct Page {
+    jobs: Vec<Job>,
+}
+
+// How the display changes over from one page to the next, each step is shown for the given time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Transition {
+    #[default]
+    Cut,
+    // The new page pushes the old one off to the left
+    Slide(Duration),
+    // The new page is drawn over the old one from left to right
+    Wipe(Duration),
+    // The display is cleared and the new page is typed out one character at a time
+    Typewriter(Duration),
+}
+
+impl Page {
+    pub fn new() -> Self {
+        Page { jobs: Vec::new() }
+    }
+
+    pub fn with_job(mut self, job: Job) -> Self {
+        self.jobs.push(job);
+        self
+    }
+
+    pub fn add_job(&mut self, job: Job) {
+        self.jobs.push(job);
+    }
+
+    pub(crate) fn into_jobs(self) -> Vec<Job> {
+        self.jobs
+    }
+}
+
+impl Transition {
+    pub fn step(&self) -> Duration {
+        match *self {
+            Transition::Cut => Duration::from_secs(0),
+            Transition::Slide(step) | Transition::Wipe(step) | Transition::Typewriter(step) => step,
+        }
+    }
+
+    // Every screen to show on the way from `before` to `after`, ending with `after`. Screens are
+    // one string per row and every row has the same length
+    pub fn frames(&self, before: &[String], after: &[String]) -> Vec<Vec<String>> {
+        let cols = after.first().map_or(0, |row| row.len());
+        match *self {
+            Transition::Cut => vec![after.to_vec()],
+            Transition::Slide(_) => (1..=cols)
+                .map(|step| {
+                    before
+                        .iter()
+                        .zip(after)
+                        .map(|(old, new)| format!("{}{}", &old[step..], &new[..step]))
+                        .collect()
+                })
+                .collect(),
+            Transition::Wipe(_) => (1..=cols)
+                .map(|step| {
+                    before
+                        .iter()
+                        .zip(after)
+                        .map(|(old, new)| format!("{}{}", &new[..step], &old[step..]))
+                        .collect()
+                })
+                .collect(),
+            Transition::Typewriter(_) => (0..=after.len() * cols)
+                .map(|typed| {
+                    after
+                        .iter()
+                        .enumerate()
+                        .map(|(row, new)| {
+                            let shown = typed.saturating_sub(row * cols).min(cols);
+                            format!("{: <width$}", &new[..shown], width = cols)
+                        })
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    // Draw the transition, only rows that change between steps are written out
+    pub fn render(
+        &self,
+        lcd: &LcdDriver,
+        before: &[String],
+        after: &[String],
+    ) -> Result<(), Error> {
+        let mut last: Vec<String> = before.to_vec();
+        for frame in self.frames(before, after) {
+            draw_changes(lcd, &last, &frame)?;
+            sleep(self.step());
+            last = frame;
+        }
+        Ok(())
+    }
+}
+
+// Write out the rows of `frame` that differ from `last`
+pub fn draw_changes(lcd: &LcdDriver, last: &[String], frame: &[String]) -> Result<(), Error> {
+    for (row, line) in frame.iter().enumerate() {
+        if last.get(row) != Some(line) {
+            lcd.set_cursor(row as u8, 0)?;
+            lcd.print(line)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pages::*;
+
+    fn screen(rows: &[&str]) -> Vec<String> {
+        rows.iter().map(|row| row.to_string()).collect()
+    }
+
+    #[test]
+    fn slide_test() {
+        let frames = Transition::Slide(Duration::from_millis(10))
+            .frames(&screen(&["abc", "def"]), &screen(&["123", "456"]));
+        assert_eq!(
+            frames,
+            vec![
+                screen(&["bc1", "ef4"]),
+                screen(&["c12", "f45"]),
+                screen(&["123", "456"])
+            ]
+        );
+    }
+
+    #[test]
+    fn wipe_test() {
+        let frames = Transition::Wipe(Duration::from_millis(10))
+            .frames(&screen(&["abc"]), &screen(&["123"]));
+        assert_eq!(
+            frames,
+            vec![screen(&["1bc"]), screen(&["12c"]), screen(&["123"])]
+        );
+    }
+
+    #[test]
+    fn typewriter_test() {
+        let frames = Transition::Typewriter(Duration::from_millis(10))
+            .frames(&screen(&["ab", "cd"]), &screen(&["12", "34"]));
+        assert_eq!(frames.len(), 5);
+        assert_eq!(frames[0], screen(&["  ", "  "]));
+        assert_eq!(frames[3], screen(&["12", "3 "]));
+        assert_eq!(frames[4], screen(&["12", "34"]));
+    }
+}
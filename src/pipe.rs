@@ -0,0 +1,225 @@
+use crate::health::WorkerState;
+use crate::scheduler::{Job, JobHandle, ThreadedLcd};
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, BufRead};
+use std::thread::sleep;
+use std::time::Duration;
+
+// Where each line of input goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    // Down the rows one line at a time, going back to the top after the bottom row
+    Roll,
+    // The last lines on the bottom rows, newest at the bottom, moving up as more come in
+    Tail(u8),
+    // The row given at the start of the line, like `1:text`. Lines without one are skipped
+    Prefix,
+}
+
+// Decides which rows change for each line of input
+#[derive(Debug, Clone)]
+pub struct Router {
+    mode: Mode,
+    rows: u8,
+    next_row: u8,
+    recent: VecDeque<String>,
+}
+
+// Shows lines of input on a `ThreadedLcd`, each row is a job so lines too long for it scroll
+pub struct Pipe<'a> {
+    lcd: &'a ThreadedLcd,
+    router: Router,
+    rate: Duration,
+    jobs: HashMap<u8, JobHandle>,
+}
+
+impl Router {
+    pub fn new(mode: Mode, rows: u8) -> Self {
+        let mode = match mode {
+            Mode::Tail(lines) => Mode::Tail(lines.max(1).min(rows)),
+            mode => mode,
+        };
+        Router {
+            mode,
+            rows,
+            next_row: 0,
+            recent: VecDeque::new(),
+        }
+    }
+
+    // The rows to change and what goes on them
+    pub fn route(&mut self, line: &str) -> Vec<(u8, String)> {
+        let line = line.trim_end_matches('\r');
+        match self.mode {
+            Mode::Roll => {
+                let row = self.next_row;
+                self.next_row = (row + 1) % self.rows.max(1);
+                vec![(row, line.to_string())]
+            }
+            Mode::Tail(lines) => {
+                self.recent.push_back(line.to_string());
+                if self.recent.len() > lines as usize {
+                    self.recent.pop_front();
+                }
+                // Until there are enough lines the top of the area stays blank
+                let blank = lines as usize - self.recent.len();
+                let first = self.rows - lines;
+                (0..lines)
+                    .map(|index| {
+                        let text = match (index as usize).checked_sub(blank) {
+                            Some(recent) => self.recent[recent].clone(),
+                            None => String::new(),
+                        };
+                        (first + index, text)
+                    })
+                    .collect()
+            }
+            Mode::Prefix => {
+                let mut parts = line.splitn(2, ':');
+                match (
+                    parts.next().map(|row| row.trim().parse::<u8>()),
+                    parts.next(),
+                ) {
+                    (Some(Ok(row)), Some(text)) if row < self.rows => {
+                        vec![(row, text.to_string())]
+                    }
+                    _ => Vec::new(),
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Pipe<'a> {
+    pub fn new(lcd: &'a ThreadedLcd, mode: Mode) -> Self {
+        Pipe {
+            router: Router::new(mode, lcd.get_rows()),
+            lcd,
+            rate: Duration::from_millis(300),
+            jobs: HashMap::new(),
+        }
+    }
+
+    // How fast lines too long for their row scroll
+    pub fn with_rate(mut self, rate: Duration) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    pub fn line(&mut self, line: &str) {
+        for (row, text) in self.router.route(line) {
+            match self.jobs.get(&row) {
+                Some(job) if job.set_text(&text) => {}
+                _ => {
+                    let job = self.lcd.add_job(self.job(row, &text));
+                    self.jobs.insert(row, job);
+                }
+            }
+        }
+    }
+
+    // Waits for the last line sent to each row to be drawn, so it isn't lost if the display is
+    // shut down straight after. Gives up if the worker has stopped, since then it never will be
+    pub fn flush(&self) {
+        while !self
+            .jobs
+            .values()
+            .all(|job| job.has_run() || !job.is_active())
+        {
+            if self.lcd.health().state == WorkerState::Stopped {
+                return;
+            }
+            sleep(Duration::from_millis(1));
+        }
+    }
+
+    // Shows every line until the input ends
+    pub fn read<R: BufRead>(&mut self, input: R) -> io::Result<()> {
+        for line in input.lines() {
+            self.line(&line?);
+        }
+        Ok(())
+    }
+
+    fn job(&self, row: u8, text: &str) -> Job {
+        Job::new(text, row, Some(self.rate))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::clock::TestClock;
+    use crate::lcd::LcdDriver;
+    use crate::pipe::*;
+    use crate::scheduler::JobQueue;
+    use crate::sim::Simulator;
+
+    #[test]
+    fn router_test() {
+        let mut roll = Router::new(Mode::Roll, 2);
+        assert_eq!(roll.route("a"), vec![(0, "a".to_string())]);
+        assert_eq!(roll.route("b\r"), vec![(1, "b".to_string())]);
+        assert_eq!(roll.route("c"), vec![(0, "c".to_string())]);
+
+        let mut tail = Router::new(Mode::Tail(2), 4);
+        assert_eq!(
+            tail.route("a"),
+            vec![(2, String::new()), (3, "a".to_string())]
+        );
+        tail.route("b");
+        assert_eq!(
+            tail.route("c"),
+            vec![(2, "b".to_string()), (3, "c".to_string())]
+        );
+
+        let mut prefix = Router::new(Mode::Prefix, 2);
+        assert_eq!(
+            prefix.route("1:up 3 days"),
+            vec![(1, "up 3 days".to_string())]
+        );
+        assert!(prefix.route("2:off the bottom").is_empty());
+        assert!(prefix.route("no row").is_empty());
+    }
+
+    #[test]
+    fn pipe_test() {
+        let simulator = Simulator::new(16, 2);
+        let lcd = ThreadedLcd::with_driver(LcdDriver::from_bus(simulator.clone(), 16, 2).unwrap());
+        // Slow enough that nothing scrolls while the test looks
+        let rate = Duration::from_secs(60 * 60);
+        let mut pipe = Pipe::new(&lcd, Mode::Tail(2)).with_rate(rate);
+        pipe.read("one\ntwo\nthree\n".as_bytes()).unwrap();
+        pipe.flush();
+        assert_eq!(
+            simulator.controller().lines('?'),
+            vec!["two             ", "three           "]
+        );
+        pipe.line("a line much too long for the display");
+        pipe.flush();
+        assert_eq!(
+            simulator.controller().lines('?'),
+            vec!["three           ", "a line much too "]
+        );
+
+        // Too long for the row, so it moves along a column each time its rate comes around
+        let clock = TestClock::new();
+        let mut queue = JobQueue::new(clock.clone());
+        queue.push(pipe.job(1, "a line much too long for the display"));
+        let mut frames = Vec::new();
+        for _ in 0..3 {
+            let mut job = queue.pop_due().unwrap();
+            frames.extend(job.render(16, &[]));
+            queue.reschedule(job);
+            assert!(queue.pop_due().is_none());
+            clock.advance(rate);
+        }
+        assert_eq!(
+            frames,
+            vec![
+                (0, "a line much too ".to_string()),
+                (0, " line much too l".to_string()),
+                (0, "line much too lo".to_string()),
+            ]
+        );
+    }
+}
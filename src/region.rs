@@ -0,0 +1,101 @@
+// A strip of cells on a single row of the display
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub row: u8,
+    pub col: u8,
+    // None runs to the end of the row, whatever size the display is
+    pub width: Option<u8>,
+}
+
+impl Region {
+    pub fn new(row: u8, col: u8, width: u8) -> Self {
+        Region {
+            row,
+            col,
+            width: Some(width),
+        }
+    }
+
+    pub fn row(row: u8) -> Self {
+        Region {
+            row,
+            col: 0,
+            width: None,
+        }
+    }
+
+    // First column and one past the last column, clipped to the display
+    pub fn span(&self, cols: u8) -> (u8, u8) {
+        let start = self.col.min(cols);
+        let end = match self.width {
+            Some(width) => self.col.saturating_add(width).min(cols),
+            None => cols,
+        };
+        (start, end)
+    }
+
+    pub fn width_on(&self, cols: u8) -> u8 {
+        let (start, end) = self.span(cols);
+        end - start
+    }
+
+    pub fn overlaps(&self, other: &Region, cols: u8) -> bool {
+        let (start, end) = self.span(cols);
+        let (other_start, other_end) = other.span(cols);
+        self.row == other.row && start < other_end && other_start < end
+    }
+
+    // The runs of columns in this region that aren't hidden by any of `covering`
+    pub fn visible_spans(&self, covering: &[Region], cols: u8) -> Vec<(u8, u8)> {
+        let (start, end) = self.span(cols);
+        let mut spans = Vec::new();
+        let mut run_start = None;
+        for col in start..=end {
+            let hidden = col == end
+                || covering.iter().any(|region| {
+                    let (covered_start, covered_end) = region.span(cols);
+                    region.row == self.row && covered_start <= col && col < covered_end
+                });
+            match (hidden, run_start) {
+                (false, None) => run_start = Some(col),
+                (true, Some(run)) => {
+                    spans.push((run, col));
+                    run_start = None;
+                }
+                _ => {}
+            }
+        }
+        spans
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::region::*;
+
+    #[test]
+    fn span_test() {
+        assert_eq!(Region::row(1).span(16), (0, 16));
+        assert_eq!(Region::new(1, 12, 4).span(16), (12, 16));
+        assert_eq!(Region::new(1, 12, 8).span(16), (12, 16));
+        assert_eq!(Region::new(1, 20, 4).span(16), (16, 16));
+    }
+
+    #[test]
+    fn visible_spans_test() {
+        let title = Region::row(0);
+        let clock = Region::new(0, 12, 4);
+        let badge = Region::new(0, 4, 2);
+        let other_row = Region::new(1, 0, 8);
+        assert_eq!(title.visible_spans(&[], 16), vec![(0, 16)]);
+        assert_eq!(
+            title.visible_spans(&[clock, badge, other_row], 16),
+            vec![(0, 4), (6, 12)]
+        );
+        assert_eq!(clock.visible_spans(&[title], 16), vec![]);
+        assert!(title.overlaps(&clock, 16));
+        assert!(!clock.overlaps(&badge, 16));
+        assert!(!title.overlaps(&other_row, 16));
+    }
+}
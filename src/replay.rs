@@ -0,0 +1,273 @@
+use crate::hd44780::Bus;
+use crate::lcd::LcdDriver;
+use crate::sim::Interface;
+use crate::trace::Op;
+use gpio_cdev::errors::Error;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, ErrorKind, Write};
+use std::path::Path;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+// Wraps any bus and writes every byte the controller receives to a recording as it's sent, with
+// the time it was sent. Put it under a driver, `ThreadedLcd` included, to capture a run for
+// `Replay`. Each line is flushed as it's written so a crash still leaves the recording behind
+pub struct RecordBus<B, W> {
+    bus: B,
+    out: W,
+    interface: Interface,
+    started: Instant,
+}
+
+// One byte from a recording
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    // From the start of the recording
+    pub at: Duration,
+    pub byte: u8,
+    pub data: bool,
+}
+
+// A recording read back in, ready to be played against any driver
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Replay {
+    steps: Vec<Step>,
+}
+
+const HEADER: &str = "# gpio_lcd recording, seconds C|D byte";
+
+impl<B: Bus> RecordBus<B, BufWriter<File>> {
+    pub fn create<P: AsRef<Path>>(bus: B, path: P) -> io::Result<Self> {
+        RecordBus::new(bus, BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<B: Bus, W: Write> RecordBus<B, W> {
+    pub fn new(bus: B, mut out: W) -> io::Result<Self> {
+        writeln!(out, "{}", HEADER)?;
+        out.flush()?;
+        Ok(RecordBus {
+            interface: Interface::settled(bus.is_eight_bit()),
+            bus,
+            out,
+            started: Instant::now(),
+        })
+    }
+
+    pub fn into_inner(self) -> B {
+        self.bus
+    }
+}
+
+impl<B, W> Bus for RecordBus<B, W>
+where
+    B: Bus,
+    B::Error: From<io::Error>,
+    W: Write,
+{
+    type Error = B::Error;
+
+    fn is_eight_bit(&self) -> bool {
+        self.bus.is_eight_bit()
+    }
+
+    fn write_bits(&mut self, bits: u8, data: bool) -> Result<(), B::Error> {
+        let eight_bit_bus = self.bus.is_eight_bit();
+        if let Some(byte) = self.interface.receive(bits, data, eight_bit_bus) {
+            let step = Step {
+                at: self.started.elapsed(),
+                byte,
+                data,
+            };
+            writeln!(self.out, "{}", step)?;
+            self.out.flush()?;
+        }
+        self.bus.write_bits(bits, data)
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        self.bus.delay_us(us)
+    }
+
+    // Reads aren't steps, only what was written gets replayed
+    fn is_busy(&mut self) -> Result<Option<bool>, B::Error> {
+        self.bus.is_busy()
+    }
+}
+
+impl std::fmt::Display for Step {
+    // The decoded operation goes after a `#` for anyone reading the file, it's ignored when loading
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}.{:06} {} {:02X} # {}",
+            self.at.as_secs(),
+            self.at.subsec_micros(),
+            if self.data { 'D' } else { 'C' },
+            self.byte,
+            Op::decode(self.byte, self.data)
+        )
+    }
+}
+
+impl Replay {
+    pub fn new(steps: Vec<Step>) -> Self {
+        Replay { steps }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Replay::read(BufReader::new(File::open(path)?))
+    }
+
+    pub fn read<R: BufRead>(reader: R) -> Result<Self, Error> {
+        let mut steps = Vec::new();
+        for (number, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            match parse_step(line) {
+                Some(step) => steps.push(step),
+                None => {
+                    return Err(Error::from(io::Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "Line {} of the recording isn't a step: {}",
+                            number + 1,
+                            line
+                        ),
+                    )))
+                }
+            }
+        }
+        Ok(Replay { steps })
+    }
+
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    // How long it took to record
+    pub fn duration(&self) -> Duration {
+        self.steps
+            .last()
+            .map_or(Duration::from_secs(0), |step| step.at)
+    }
+
+    // Sends the recording to the driver, `speed` times as fast as it was recorded, or as fast as
+    // the display takes it with `f64::INFINITY`. Function sets are left out as they're the
+    // target's own business, its init has already set up its bus. Clears and homes go through the
+    // driver so real hardware gets the time it needs for them
+    pub fn play(&self, lcd: &LcdDriver, speed: f64) -> Result<(), Error> {
+        let started = Instant::now();
+        for step in &self.steps {
+            if speed.is_finite() && speed > 0.0 {
+                let due = step.at.div_f64(speed);
+                let elapsed = started.elapsed();
+                if due > elapsed {
+                    sleep(due - elapsed);
+                }
+            }
+            match Op::decode(step.byte, step.data) {
+                Op::FunctionSet { .. } => {}
+                Op::Clear => lcd.clear()?,
+                Op::Home => lcd.home()?,
+                Op::Write(byte) => lcd.write(byte)?,
+                _ => lcd.command(step.byte)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_step(line: &str) -> Option<Step> {
+    let mut fields = line.split_whitespace();
+    let at = fields.next()?.parse::<f64>().ok()?;
+    let data = match fields.next()? {
+        "C" => false,
+        "D" => true,
+        _ => return None,
+    };
+    let byte = u8::from_str_radix(fields.next()?, 16).ok()?;
+    if fields.next().is_some() || !at.is_finite() || at < 0.0 {
+        return None;
+    }
+    Some(Step {
+        at: Duration::from_secs_f64(at),
+        byte,
+        data,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::replay::*;
+    use crate::scheduler::{Job, Shutdown, ThreadedLcd};
+    use crate::sim::Simulator;
+    use std::sync::{Arc, Mutex};
+
+    // Collects the recording so it can be read while the driver still owns the bus
+    #[derive(Clone, Default)]
+    struct Output(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn step_test() {
+        let step = Step {
+            at: Duration::from_micros(4_512),
+            byte: 0xC0,
+            data: false,
+        };
+        assert_eq!(step.to_string(), "0.004512 C C0 # SET_DDRAM 0x40");
+        let replay =
+            Replay::read(format!("{}\n\n{}\n0.1 D 41\n", HEADER, step).as_bytes()).unwrap();
+        assert_eq!(replay.steps()[0], step);
+        assert_eq!(replay.steps()[1].byte, b'A');
+        assert_eq!(replay.duration(), Duration::from_millis(100));
+        assert!(Replay::read("0.1 X 41\n".as_bytes()).is_err());
+        assert!(Replay::read("0.1 D 41 42\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn replay_test() {
+        // Recorded from a driver that's already running, on an 8 bit bus
+        let output = Output::default();
+        let recorded = Simulator::new(16, 2);
+        let lcd = LcdDriver::from_bus(recorded.clone(), 16, 2)
+            .unwrap()
+            .map_bus(|bus| RecordBus::new(bus, output.clone()).unwrap());
+        let lcd = ThreadedLcd::with_driver(lcd);
+        lcd.add_job(Job::new("recorded", 0, None));
+        lcd.add_job(Job::new("a longer line to scroll", 1, None));
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while recorded.controller().lines('?')[1].trim().is_empty() {
+            assert!(Instant::now() < deadline, "the jobs never ran");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(recorded.controller().lines('?')[0], "recorded        ");
+        lcd.shutdown(Shutdown::default()).unwrap();
+
+        // Played back on a 4 bit bus
+        let replay = Replay::read(output.0.lock().unwrap().as_slice()).unwrap();
+        assert!(!replay.steps().is_empty());
+        let played = Simulator::new(16, 2).four_bit();
+        let target = LcdDriver::from_bus(played.clone(), 16, 2).unwrap();
+        replay.play(&target, f64::INFINITY).unwrap();
+        assert_eq!(
+            played.controller().lines('?'),
+            recorded.controller().lines('?')
+        );
+        assert_eq!(played.controller().cursor(), recorded.controller().cursor());
+    }
+}
@@ -0,0 +1,274 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike};
+use chrono::{Datelike, Duration as ChronoDuration};
+use gpio_cdev::errors::Error;
+use std::io::ErrorKind;
+use std::time::Duration;
+
+// When a job runs after its first run, in local time. Takes the place of the job's rate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Schedule {
+    // On every multiple of the period counted from local midnight, so `Duration::from_secs(60)`
+    // runs on the minute. Periods that don't divide a day evenly line up again at midnight
+    Aligned(Duration),
+    Cron(Cron),
+}
+
+// A cron expression, either the usual five fields (minute, hour, day of month, month, day of
+// week) or six with seconds first. Fields take numbers, `*`, ranges `a-b`, steps `*/n` or `a-b/n`
+// and comma separated lists. Sunday is 0 or 7. As with cron, when both the day of month and day of
+// week are restricted a day matching either one will do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cron {
+    seconds: u64,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+// Give up looking for a matching day after this long, long enough for Feb 29 on a given weekday
+const SEARCH_DAYS: u32 = 366 * 28;
+
+impl Schedule {
+    pub fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        match self {
+            Schedule::Aligned(period) => aligned_after(*period, after),
+            Schedule::Cron(cron) => cron.next_after(after),
+        }
+    }
+}
+
+impl From<Cron> for Schedule {
+    fn from(cron: Cron) -> Self {
+        Schedule::Cron(cron)
+    }
+}
+
+impl Cron {
+    pub fn parse(expr: &str) -> Result<Self, Error> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let fields = match fields.len() {
+            5 => [&["0"], &fields[..]].concat(),
+            6 => fields,
+            _ => return Err(invalid("Cron expressions need 5 or 6 fields")),
+        };
+        let weekdays = parse_field(fields[5], 0, 7)?;
+        Ok(Cron {
+            seconds: parse_field(fields[0], 0, 59)?,
+            minutes: parse_field(fields[1], 0, 59)?,
+            hours: parse_field(fields[2], 0, 23)?,
+            days: parse_field(fields[3], 1, 31)?,
+            months: parse_field(fields[4], 1, 12)?,
+            // Fold Sunday as 7 onto 0
+            weekdays: (weekdays | weekdays >> 7) & 0x7f,
+            any_day: fields[3] == "*",
+            any_weekday: fields[5] == "*",
+        })
+    }
+
+    // The first time strictly after `after` that matches, skipping times that don't exist
+    // because of a daylight saving change
+    pub fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let start = after.naive_local().with_nanosecond(0)? + ChronoDuration::seconds(1);
+        let mut date = start.date();
+        for _ in 0..SEARCH_DAYS {
+            if self.matches_day(date) {
+                let from = if date == start.date() {
+                    start.time()
+                } else {
+                    NaiveTime::MIN
+                };
+                if let Some(next) = self.first_time_on(date, from, after) {
+                    return Some(next);
+                }
+            }
+            date = date.succ_opt()?;
+        }
+        None
+    }
+
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        let day = has(self.days, date.day());
+        let weekday = has(self.weekdays, date.weekday().num_days_from_sunday());
+        if !has(self.months, date.month()) {
+            return false;
+        }
+        match (self.any_day, self.any_weekday) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+
+    fn first_time_on(
+        &self,
+        date: NaiveDate,
+        from: NaiveTime,
+        after: DateTime<Local>,
+    ) -> Option<DateTime<Local>> {
+        for hour in (from.hour()..24).filter(|&hour| has(self.hours, hour)) {
+            let first_minute = if hour == from.hour() {
+                from.minute()
+            } else {
+                0
+            };
+            for minute in (first_minute..60).filter(|&minute| has(self.minutes, minute)) {
+                let first_second = if (hour, minute) == (from.hour(), from.minute()) {
+                    from.second()
+                } else {
+                    0
+                };
+                for second in (first_second..60).filter(|&second| has(self.seconds, second)) {
+                    let time =
+                        NaiveDateTime::new(date, NaiveTime::from_hms_opt(hour, minute, second)?);
+                    match Local.from_local_datetime(&time).earliest() {
+                        Some(next) if next > after => return Some(next),
+                        _ => {}
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+fn has(field: u64, value: u32) -> bool {
+    field & (1 << value) != 0
+}
+
+fn invalid(msg: &str) -> Error {
+    Error::from(std::io::Error::new(ErrorKind::InvalidInput, msg))
+}
+
+// Each value in the field as a bit
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, Error> {
+    let mut bits = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.find('/') {
+            Some(slash) => (&part[..slash], parse_number(&part[slash + 1..])?),
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some(dash) = range.find('-') {
+            (
+                parse_number(&range[..dash])?,
+                parse_number(&range[dash + 1..])?,
+            )
+        } else {
+            let start = parse_number(range)?;
+            // `a/n` runs from `a` to the end of the field
+            (start, if step > 1 { max } else { start })
+        };
+        if start < min || end > max || start > end || step == 0 {
+            return Err(invalid("Cron field out of range"));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+fn parse_number(text: &str) -> Result<u32, Error> {
+    text.parse()
+        .map_err(|_| invalid("Cron fields must be numbers"))
+}
+
+fn aligned_after(period: Duration, after: DateTime<Local>) -> Option<DateTime<Local>> {
+    let period = ChronoDuration::from_std(period).ok()?;
+    if period <= ChronoDuration::zero() {
+        return None;
+    }
+    let midnight = after.date_naive().and_time(NaiveTime::MIN);
+    let since_midnight = after.naive_local() - midnight;
+    // Periods too long to count in nanoseconds or landing past the end of time have no next run
+    let period_nanos = period.num_nanoseconds()?;
+    let periods = since_midnight.num_nanoseconds()? / period_nanos + 1;
+    let offset = ChronoDuration::nanoseconds(periods.checked_mul(period_nanos)?);
+    let mut next = midnight.checked_add_signed(offset)?;
+    // Boundaries that fall in a daylight saving gap are skipped
+    loop {
+        match Local.from_local_datetime(&next).earliest() {
+            Some(next) if next > after => return Some(next),
+            _ => next = next.checked_add_signed(period)?,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::schedule::*;
+
+    fn local(hour: u32, min: u32, sec: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2020, 6, 1, hour, min, sec).unwrap()
+    }
+
+    #[test]
+    fn cron_parse_test() {
+        assert!(Cron::parse("* * * * *").is_ok());
+        assert!(Cron::parse("*/5 * * * * *").is_ok());
+        assert!(Cron::parse("0 6,14,22 * * 1-5").is_ok());
+        assert!(Cron::parse("* * *").is_err());
+        assert!(Cron::parse("60 * * * *").is_err());
+        assert!(Cron::parse("5-1 * * * *").is_err());
+        assert!(Cron::parse("*/0 * * * *").is_err());
+        assert!(Cron::parse("a * * * *").is_err());
+    }
+
+    #[test]
+    fn cron_next_test() {
+        let shifts = Cron::parse("0 6,14,22 * * *").unwrap();
+        assert_eq!(shifts.next_after(local(5, 59, 59)), Some(local(6, 0, 0)));
+        assert_eq!(shifts.next_after(local(6, 0, 0)), Some(local(14, 0, 0)));
+        assert_eq!(
+            shifts.next_after(local(23, 0, 0)),
+            Some(Local.with_ymd_and_hms(2020, 6, 2, 6, 0, 0).unwrap())
+        );
+        let seconds = Cron::parse("*/15 * * * * *").unwrap();
+        assert_eq!(seconds.next_after(local(8, 59, 50)), Some(local(9, 0, 0)));
+        // 2020-06-01 was a Monday, so the first weekend day is the Saturday
+        let weekend = Cron::parse("30 9 * * 0,6").unwrap();
+        assert_eq!(
+            weekend.next_after(local(0, 0, 0)),
+            Some(Local.with_ymd_and_hms(2020, 6, 6, 9, 30, 0).unwrap())
+        );
+        // Either the 15th or a Sunday
+        let either = Cron::parse("0 0 15 * 7").unwrap();
+        assert_eq!(
+            either.next_after(local(0, 0, 0)),
+            Some(Local.with_ymd_and_hms(2020, 6, 7, 0, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn aligned_test() {
+        let minute = Schedule::Aligned(Duration::from_secs(60));
+        assert_eq!(minute.next_after(local(8, 59, 59)), Some(local(9, 0, 0)));
+        assert_eq!(minute.next_after(local(9, 0, 0)), Some(local(9, 1, 0)));
+        let quarter = Schedule::Aligned(Duration::from_secs(15 * 60));
+        assert_eq!(quarter.next_after(local(9, 7, 3)), Some(local(9, 15, 0)));
+        assert_eq!(
+            Schedule::Aligned(Duration::from_secs(0)).next_after(local(9, 0, 0)),
+            None
+        );
+
+        // Counting the periods since midnight doesn't wrap for short periods or overflow for
+        // long ones
+        assert_eq!(
+            Schedule::Aligned(Duration::from_nanos(1)).next_after(local(9, 0, 0)),
+            Some(local(9, 0, 0) + ChronoDuration::nanoseconds(1))
+        );
+        let day = Schedule::Aligned(Duration::from_secs(24 * 60 * 60));
+        assert_eq!(
+            day.next_after(local(9, 0, 0)),
+            Some(local(0, 0, 0) + ChronoDuration::days(1))
+        );
+        let millennium = Schedule::Aligned(Duration::from_secs(1000 * 365 * 24 * 60 * 60));
+        assert_eq!(millennium.next_after(local(9, 0, 0)), None);
+        let forever = Schedule::Aligned(Duration::from_secs(u64::MAX));
+        assert_eq!(forever.next_after(local(9, 0, 0)), None);
+    }
+}
@@ -1,31 +1,175 @@
+use crate::clock::{Clock, SystemClock};
+use crate::health::{ErrorPolicy, Health, Recovery, WorkerState};
+use crate::icons::Icon;
 use crate::lcd::LcdDriver;
-use parking_lot::Mutex;
-use std::cmp::Ordering;
-use std::collections::BinaryHeap;
-use std::sync::atomic::Ordering::AcqRel;
-use std::sync::mpsc::{channel, Receiver, Sender};
+use crate::notify::{Notification, NotificationId};
+use crate::pages::{Page, PageId, Transition};
+use crate::region::Region;
+use crate::schedule::Schedule;
+use crate::scroll::Scroller;
+use chrono::{DateTime, Local};
+use gpio_cdev::errors::Error;
+use parking_lot::{Condvar, Mutex, MutexGuard};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
 use std::sync::Arc;
 use std::thread;
-use std::thread::{sleep, JoinHandle};
+use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
 use unidecode::unidecode;
 
+pub type JobId = u64;
+
 pub struct ThreadedLcd {
     lcd_driver: Arc<Mutex<LcdDriver>>,
-    job_list: Arc<Mutex<Vec<Job>>>,
-    execution_thread: JoinHandle<()>,
+    job_queue: Arc<Mutex<JobQueue>>,
+    // Signalled whenever the job queue changes so the worker can re-check what to run next
+    job_signal: Arc<Condvar>,
+    // Only set while holding the job queue lock, so the worker can't miss it
+    stopping: Arc<AtomicBool>,
+    supervisor: Arc<Mutex<Supervisor>>,
+    // Taken once the worker has been stopped
+    execution_thread: Option<JoinHandle<()>>,
+    on_drop: Shutdown,
+}
+
+// Called from the worker with every error it runs into
+type ErrorFn = Box<dyn FnMut(&Error) + Send>;
+
+// Keeps track of how the worker is doing and decides what it does about errors
+pub(crate) struct Supervisor {
+    pub(crate) policy: ErrorPolicy,
+    pub(crate) health: Health,
+    pub(crate) on_error: Option<ErrorFn>,
+}
+
+// Wakes up whichever kind of worker is running the queue after it changes
+#[derive(Clone)]
+pub(crate) enum Signal {
+    Thread(Arc<Condvar>),
+    #[cfg(feature = "tokio")]
+    Task(Arc<tokio::sync::Notify>),
+}
+
+// Everything the worker thread needs besides the job queue, which it keeps locked
+struct Worker {
+    lcd_driver: Arc<Mutex<LcdDriver>>,
+    job_signal: Arc<Condvar>,
+    stopping: Arc<AtomicBool>,
+    supervisor: Arc<Mutex<Supervisor>>,
+}
+
+// What to leave on the display once the worker has stopped
+#[derive(Debug, Clone, Default)]
+pub struct Shutdown {
+    clear: bool,
+    farewell: Option<String>,
+    display_off: bool,
+    backlight_off: bool,
 }
 
-#[derive(Debug, Clone)]
+// Produces the text for a dynamic job every time it runs
+pub(crate) type ContentFn = Box<dyn FnMut() -> String + Send>;
+
 pub struct Job {
     text: String,
-    row: u8,
-    index: i32,
+    content: Option<ContentFn>,
+    region: Region,
+    // Jobs on a higher layer hide the parts of lower jobs they overlap. On the same layer the job
+    // added last is on top
+    layer: i32,
+    scroll: Scroller,
     rate: Option<Duration>,
-    last_run: Option<Instant>,
+    // Takes the place of the rate when set
+    schedule: Option<Schedule>,
+    // The job waits for `start_at` before its first run and is dropped once its next run would be
+    // after `stop_at`, or once it has run `runs_left` more times
+    start_at: Option<DateTime<Local>>,
+    stop_at: Option<DateTime<Local>>,
+    runs_left: Option<u32>,
+    // Filled in by the queue
+    id: JobId,
+    page: Option<PageId>,
+    seq: u64,
+    next_run: Option<Instant>,
+    // Set once the job has run, and cleared again when its text is changed
+    has_run: bool,
+    // While `content` is out with the worker, and once it has been called for the next run
+    fetching: bool,
+    fetched: bool,
+}
+
+// A piece of dynamic content, icons must be loaded in CGRAM to show up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    Text(String),
+    Icon(Icon),
+}
+
+// Returned from `add_job` to change a job after it has been queued. Every change is
+// made under the queue lock, so the worker sees it all at once on its next tick. One off jobs
+// stay around after they have run so they can still be changed, and run again when they are.
+// Methods return false once the job is gone (cancelled, cleared, out of runs, or a one off
+// hidden completely by later jobs)
+#[derive(Clone)]
+pub struct JobHandle {
+    id: JobId,
+    job_queue: Arc<Mutex<JobQueue>>,
+    signal: Signal,
+}
+
+// Jobs ordered by when they should next run. Ties go to one off jobs, then to whichever was
+// scheduled first, so the order never depends on when it is looked at
+pub struct JobQueue<C: Clock = SystemClock> {
+    clock: C,
+    deadlines: BinaryHeap<Reverse<Deadline>>,
+    jobs: HashMap<JobId, Job>,
+    // Paused jobs have no deadline until they are resumed
+    paused: HashMap<JobId, Job>,
+    // Jobs waiting for their page to be shown, along with one off jobs that have already run
+    parked: HashMap<JobId, Job>,
+    pages: Vec<PageId>,
+    active_page: Option<PageId>,
+    // Set when the worker should change pages, `Some(None)` blanks out the page entirely
+    page_switch: Option<Option<PageId>>,
+    rotation: Option<Duration>,
+    next_rotation: Option<Instant>,
+    transition: Transition,
+    // Jobs and page changes are held back while a notification is showing
+    notifications: BinaryHeap<PendingNotification>,
+    notification: Option<ActiveNotification>,
+    next_id: JobId,
+    next_seq: u64,
+}
+
+struct PendingNotification {
+    id: NotificationId,
+    seq: u64,
+    notification: Notification,
+}
+
+struct ActiveNotification {
+    id: NotificationId,
+    notification: Notification,
+    until: Instant,
+    visible: bool,
+    next_blink: Option<Instant>,
+}
+
+// Entries are left in the heap when their job is removed or rescheduled, and are skipped once
+// their seq no longer matches the job's
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Deadline {
+    at: Instant,
+    periodic: bool,
+    seq: u64,
+    id: JobId,
 }
 
 impl ThreadedLcd {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         cols: u8,
         rows: u8,
@@ -66,258 +210,1837 @@ impl ThreadedLcd {
     }
 
     pub fn with_driver(lcd: LcdDriver) -> Self {
-        // Interesting idea would be to make this a hashmap based on the interval between jobs and execute on that key....
-        let job_list = Arc::new(Mutex::new(Vec::<Job>::new()));
+        let job_queue = Arc::new(Mutex::new(JobQueue::new(SystemClock)));
+        let job_signal = Arc::new(Condvar::new());
         let lcd_driver = Arc::new(Mutex::new(lcd));
-        let thread_job_list = Arc::clone(&job_list);
-        let thread_lcd_driver = Arc::clone(&lcd_driver);
-        let execution_thread = thread::spawn(move || loop {
-            let mut job_list = thread_job_list.lock();
-            match job_list.first_mut() {
-                Some(job) => {
-                    // Wait for delay, so we run on time
-                    if job.last_run.is_some() {
-                        let sleep_time = job
-                            .rate
-                            .unwrap()
-                            .checked_sub(job.last_run.unwrap().elapsed());
-                        if sleep_time.is_some() {
-                            sleep(sleep_time.unwrap());
-                        }
+        let stopping = Arc::new(AtomicBool::new(false));
+        let supervisor = Arc::new(Mutex::new(Supervisor::new()));
+        let thread_job_queue = Arc::clone(&job_queue);
+        let worker = Worker {
+            lcd_driver: Arc::clone(&lcd_driver),
+            job_signal: Arc::clone(&job_signal),
+            stopping: Arc::clone(&stopping),
+            supervisor: Arc::clone(&supervisor),
+        };
+        let execution_thread = thread::spawn(move || {
+            let (cols, rows) = {
+                let driver = worker.lcd_driver.lock();
+                (driver.get_cols(), driver.get_rows())
+            };
+            let mut job_queue = thread_job_queue.lock();
+            while !worker.stopping.load(AtomicOrdering::SeqCst) {
+                if let Some(screen) = job_queue.notification_update(cols, rows) {
+                    let result = draw_screen(&worker.lcd_driver.lock(), &screen);
+                    if !worker.check(&mut job_queue, result) {
+                        break;
                     }
-                    // Pass the cloned Arc to the lcd_driver
-                    job.run(thread_lcd_driver.clone());
-                    // Run the job
-                    (*job).last_run = Some(Instant::now());
-                    // Remove from queue if it's a one off
-                    if job.rate.is_none() {
-                        job_list.remove(0);
+                    continue;
+                }
+                if let Some(page) = job_queue.take_page_switch() {
+                    let before = job_queue.compose(cols, rows);
+                    job_queue.show_page_now(page);
+                    let after = job_queue.compose(cols, rows);
+                    let transition = job_queue.transition;
+                    // Transitions take a while, don't hold up anyone adding jobs
+                    let result = MutexGuard::unlocked(&mut job_queue, || {
+                        transition.render(&worker.lcd_driver.lock(), &before, &after)
+                    });
+                    if !worker.check(&mut job_queue, result) {
+                        break;
+                    }
+                    continue;
+                }
+                // The lock is released while waiting, so the queue may have changed by the time
+                // we wake up and everything gets checked again
+                match job_queue.time_until_next() {
+                    // Nothing to do, sleep until a job gets added
+                    None => worker.job_signal.wait(&mut job_queue),
+                    Some(wait_time) if wait_time > Duration::from_secs(0) => {
+                        worker.job_signal.wait_for(&mut job_queue, wait_time);
+                    }
+                    Some(_) => {
+                        // Dynamic content may be slow to fetch or use the scheduler itself, so
+                        // it's called without the queue locked. The job stays in the queue
+                        if let Some((id, mut content)) = job_queue.take_due_content() {
+                            let text = MutexGuard::unlocked(&mut job_queue, &mut content);
+                            job_queue.put_content(id, content, text);
+                            continue;
+                        }
+                        if let Some(mut job) = job_queue.pop_due() {
+                            let covering = job_queue.covering(&job);
+                            let (id, row) = (job.id, job.region.row);
+                            let spans = job.render(cols, &covering);
+                            job_queue.reschedule(job);
+                            // The driver is taken before letting go of the queue, so `clear` can't
+                            // blank the display in between and have this drawn over it
+                            let lcd_driver = worker.lcd_driver.lock();
+                            let result = MutexGuard::unlocked(&mut job_queue, || {
+                                draw_spans(&lcd_driver, row, &spans)
+                            });
+                            drop(lcd_driver);
+                            if result.is_err() {
+                                job_queue.retry_id(id);
+                            }
+                            if !worker.check(&mut job_queue, result) {
+                                break;
+                            }
+                        }
                     }
-                    // Sort so we get the next one on top
-                    job_list.sort();
                 }
-                // This is not ideal cuz busy wait
-                None => {}
             }
         });
         ThreadedLcd {
-            job_list,
+            job_queue,
+            job_signal,
             lcd_driver,
-            execution_thread,
+            stopping,
+            supervisor,
+            execution_thread: Some(execution_thread),
+            on_drop: Shutdown::default(),
         }
     }
-    pub fn add_job(&self, job: Job) {
-        let mut job_list = self.job_list.lock();
-        job_list.push(job);
-        job_list.sort()
+
+    // Stop the worker and hand the driver back, the job queue is left as it was
+    pub fn shutdown(mut self, options: Shutdown) -> Result<LcdDriver, Error> {
+        self.stop();
+        let lcd_driver = Arc::clone(&self.lcd_driver);
+        drop(self);
+        let mut lcd_driver = match Arc::try_unwrap(lcd_driver) {
+            Ok(lcd_driver) => lcd_driver.into_inner(),
+            Err(_) => unreachable!("the LCD driver is only shared with the worker"),
+        };
+        options.apply(&mut lcd_driver)?;
+        Ok(lcd_driver)
+    }
+
+    // How to leave the display if this is dropped without calling `shutdown`
+    pub fn on_drop(&mut self, options: Shutdown) {
+        self.on_drop = options;
+    }
+
+    fn stop(&mut self) {
+        if let Some(execution_thread) = self.execution_thread.take() {
+            {
+                let _job_queue = self.job_queue.lock();
+                self.stopping.store(true, AtomicOrdering::SeqCst);
+                self.job_signal.notify_one();
+            }
+            // A worker that panicked has already stopped, which is all we need
+            let _ = execution_thread.join();
+            self.supervisor.lock().health.state = WorkerState::Stopped;
+        }
+    }
+
+    pub fn set_error_policy(&self, policy: ErrorPolicy) {
+        self.supervisor.lock().policy = policy;
+    }
+
+    // The handler runs on the worker thread before it backs off, so it shouldn't block for long.
+    // Nothing is locked while it runs, so it's free to add jobs or check the health
+    pub fn on_error<F>(&self, handler: F)
+    where
+        F: FnMut(&Error) + Send + 'static,
+    {
+        self.supervisor.lock().on_error = Some(Box::new(handler));
+    }
+
+    pub fn health(&self) -> Health {
+        self.supervisor.lock().health.clone()
+    }
+
+    pub fn add_job(&self, job: Job) -> JobHandle {
+        let id = self.job_queue.lock().push(job);
+        self.job_signal.notify_one();
+        JobHandle::new(
+            id,
+            Arc::clone(&self.job_queue),
+            Signal::Thread(Arc::clone(&self.job_signal)),
+        )
+    }
+
+    // The first page added is shown straight away
+    pub fn add_page(&self, page: Page) -> PageId {
+        let id = self.job_queue.lock().add_page(page);
+        self.job_signal.notify_one();
+        id
     }
+
+    pub fn add_page_job(&self, page: PageId, job: Job) -> Option<JobHandle> {
+        let id = self.job_queue.lock().push_to_page(page, job)?;
+        self.job_signal.notify_one();
+        Some(JobHandle::new(
+            id,
+            Arc::clone(&self.job_queue),
+            Signal::Thread(Arc::clone(&self.job_signal)),
+        ))
+    }
+
+    pub fn remove_page(&self, page: PageId) -> bool {
+        let removed = self.job_queue.lock().remove_page(page);
+        self.job_signal.notify_one();
+        removed
+    }
+
+    pub fn show_page(&self, page: PageId) -> bool {
+        let found = self.job_queue.lock().show_page(page);
+        self.job_signal.notify_one();
+        found
+    }
+
+    pub fn next_page(&self) {
+        self.job_queue.lock().next_page();
+        self.job_signal.notify_one();
+    }
+
+    pub fn current_page(&self) -> Option<PageId> {
+        self.job_queue.lock().active_page
+    }
+
+    // Move on to the next page every `interval`, or stay put with None
+    pub fn set_rotation(&self, interval: Option<Duration>) {
+        self.job_queue.lock().set_rotation(interval);
+        self.job_signal.notify_one();
+    }
+
+    pub fn set_transition(&self, transition: Transition) {
+        self.job_queue.lock().set_transition(transition);
+    }
+
+    // Notifications wait their turn behind any that are already showing
+    pub fn notify(&self, notification: Notification) -> NotificationId {
+        let id = self.job_queue.lock().notify(notification);
+        self.job_signal.notify_one();
+        id
+    }
+
+    pub fn dismiss(&self, id: NotificationId) -> bool {
+        let dismissed = self.job_queue.lock().dismiss(id);
+        self.job_signal.notify_one();
+        dismissed
+    }
+
     pub fn clear_jobs(&self) {
-        self.job_list.lock().clear();
+        self.job_queue.lock().clear();
+        self.job_signal.notify_one();
     }
 
     pub fn clear_row(&self, row: u8) {
-        self.job_list.lock().retain(|job| job.row != row);
+        self.job_queue.lock().retain(|job| job.region.row != row);
         self.add_job(Job::new("", row, None));
     }
+
+    // Puts a row back after jobs on it have been cancelled. The jobs left on it are drawn again
+    // and whatever none of them cover is blanked
+    pub fn redraw_row(&self, row: u8) -> Result<(), Error> {
+        let mut job_queue = self.job_queue.lock();
+        let lcd_driver = self.lcd_driver.lock();
+        let gaps = job_queue.redraw_row(row, lcd_driver.get_cols());
+        // A notification puts everything back by itself once it's over
+        let result = if job_queue.notification_showing() {
+            Ok(())
+        } else {
+            let blanks: Vec<(u8, String)> = gaps
+                .into_iter()
+                .map(|(start, end)| (start, " ".repeat((end - start) as usize)))
+                .collect();
+            draw_spans(&lcd_driver, row, &blanks)
+        };
+        drop(lcd_driver);
+        drop(job_queue);
+        self.job_signal.notify_one();
+        result
+    }
+
+    // Drops every job and blanks the display
+    pub fn clear(&self) -> Result<(), Error> {
+        // Locked in the same order as the worker, which holds on to the driver from working out
+        // what to draw until it's drawn
+        let mut job_queue = self.job_queue.lock();
+        job_queue.clear();
+        let result = self.lcd_driver.lock().clear();
+        drop(job_queue);
+        self.job_signal.notify_one();
+        result
+    }
+
+    pub fn backlight(&self, on: bool) -> Result<(), Error> {
+        self.lcd_driver.lock().backlight(on)
+    }
+
+    pub fn get_cols(&self) -> u8 {
+        self.lcd_driver.lock().get_cols()
+    }
+
+    pub fn get_rows(&self) -> u8 {
+        self.lcd_driver.lock().get_rows()
+    }
 }
 
-impl Job {
-    pub fn new(text: &str, row: u8, rate: Option<Duration>) -> Self {
-        Job {
-            text: unidecode(text).to_string(),
-            row,
-            index: 0,
-            rate,
-            last_run: None,
+impl Drop for ThreadedLcd {
+    fn drop(&mut self) {
+        if self.execution_thread.is_some() {
+            self.stop();
+            let _ = self.on_drop.apply(&mut self.lcd_driver.lock());
         }
     }
+}
 
-    pub fn empty(row: u8) -> Self {
-        Job {
-            text: "".to_string(),
-            row,
-            index: 0,
-            rate: None,
-            last_run: None,
-        }
-    }
-
-    pub fn run(&mut self, driver: Arc<Mutex<LcdDriver>>) {
-        let driver = driver.lock();
-        driver.set_cursor(self.row, 0).unwrap();
-        let formatted_string = if self.text.len() <= driver.get_cols() as usize {
-            format!(
-                "{: <width$}",
-                self.text.clone(),
-                width = driver.get_cols() as usize
-            )
-        } else if self.text.len() < (self.index + driver.get_cols() as i32) as usize {
-            format!(
-                "{: <width$}",
-                self.text.clone().as_str()[self.index as usize..self.text.len()].to_string(),
-                width = driver.get_cols() as usize,
-            )
-        } else if self.index < 0 {
-            let slice = self.text.clone().as_str()
-                [0..(self.index + driver.get_cols() as i32) as usize]
-                .to_string();
-            format!("{: >width$}", slice, width = driver.get_cols() as usize)
+impl Worker {
+    // Records how a write to the display went and deals with any error according to the policy.
+    // Returns false if the worker should stop
+    fn check(&self, job_queue: &mut MutexGuard<JobQueue>, mut result: Result<(), Error>) -> bool {
+        loop {
+            // The error handler is called without the queue locked, so it can use the display
+            let recovery = match result {
+                Ok(()) => Supervisor::report(&self.supervisor, result),
+                Err(_) => {
+                    MutexGuard::unlocked(job_queue, || Supervisor::report(&self.supervisor, result))
+                }
+            };
+            let backoff = match recovery {
+                None => return true,
+                Some(Recovery::Retry(backoff)) | Some(Recovery::Reinitialize(backoff)) => backoff,
+                Some(Recovery::Stop) => return false,
+            };
+            // Waiting on the signal lets jobs be queued up in the meantime
+            let until = Instant::now() + backoff;
+            while !self.stopping.load(AtomicOrdering::SeqCst) && Instant::now() < until {
+                self.job_signal.wait_until(job_queue, until);
+            }
+            let reinitialize = matches!(
+                self.supervisor.lock().policy,
+                ErrorPolicy::Reinitialize { .. }
+            );
+            if !reinitialize || self.stopping.load(AtomicOrdering::SeqCst) {
+                return true;
+            }
+            let mut lcd_driver = self.lcd_driver.lock();
+            result = lcd_driver.init();
+            // Reinitializing blanks the display, one off jobs won't run again to put themselves
+            // back so everything is drawn from the queue
+            if result.is_ok() {
+                let screen = job_queue.screen(lcd_driver.get_cols(), lcd_driver.get_rows());
+                result = draw_screen(&lcd_driver, &screen);
+            }
+        }
+    }
+}
+
+impl Supervisor {
+    pub(crate) fn new() -> Self {
+        Supervisor {
+            policy: ErrorPolicy::default(),
+            health: Health::new(),
+            on_error: None,
+        }
+    }
+
+    // Records how a write to the display went, returning what to do about it if it failed
+    pub(crate) fn report(supervisor: &Mutex<Self>, result: Result<(), Error>) -> Option<Recovery> {
+        let error = match result {
+            Ok(()) => {
+                supervisor.lock().health.record_ok(Instant::now());
+                return None;
+            }
+            Err(error) => error,
+        };
+        let (recovery, on_error) = {
+            let mut supervisor = supervisor.lock();
+            let policy = supervisor.policy;
+            let recovery = supervisor
+                .health
+                .record_error(&error, policy, Instant::now());
+            (recovery, supervisor.on_error.take())
+        };
+        // The handler is called without the supervisor locked so it can check the health
+        if let Some(mut on_error) = on_error {
+            on_error(&error);
+            supervisor.lock().on_error.get_or_insert(on_error);
+        }
+        Some(recovery)
+    }
+}
+
+impl Signal {
+    pub(crate) fn notify(&self) {
+        match self {
+            Signal::Thread(condvar) => {
+                condvar.notify_one();
+            }
+            #[cfg(feature = "tokio")]
+            Signal::Task(notify) => notify.notify_one(),
+        }
+    }
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Shutdown::default()
+    }
+
+    pub fn clear(mut self) -> Self {
+        self.clear = true;
+        self
+    }
+
+    // Written from the top left corner after clearing the display, `\n` moves to the next row
+    pub fn farewell(mut self, text: &str) -> Self {
+        self.clear = true;
+        self.farewell = Some(text.to_string());
+        self
+    }
+
+    pub fn display_off(mut self) -> Self {
+        self.display_off = true;
+        self
+    }
+
+    pub fn backlight_off(mut self) -> Self {
+        self.backlight_off = true;
+        self
+    }
+
+    pub(crate) fn apply(&self, lcd: &mut LcdDriver) -> Result<(), Error> {
+        if self.clear {
+            lcd.clear()?;
+        }
+        if let Some(farewell) = self.farewell.as_ref() {
+            for (row, line) in farewell.lines().take(lcd.get_rows() as usize).enumerate() {
+                lcd.set_cursor(row as u8, 0)?;
+                lcd.print(line)?;
+            }
+        }
+        if self.display_off {
+            lcd.no_display()?;
+        }
+        if self.backlight_off {
+            lcd.backlight(false)?;
+        }
+        Ok(())
+    }
+}
+
+impl JobHandle {
+    pub(crate) fn new(id: JobId, job_queue: Arc<Mutex<JobQueue>>, signal: Signal) -> Self {
+        JobHandle {
+            id,
+            job_queue,
+            signal,
+        }
+    }
+
+    pub fn id(&self) -> JobId {
+        self.id
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.job_queue.lock().contains(self.id)
+    }
+
+    // Whether the job has been drawn since it was added or its text was last set, so callers can
+    // wait for what they sent to show up
+    pub fn has_run(&self) -> bool {
+        self.job_queue
+            .lock()
+            .get(self.id)
+            .is_some_and(|job| job.has_run)
+    }
+
+    // Swap the text shown by the job, this turns a dynamic job into a static one. Scrolling
+    // starts over from the beginning of the new text, which is drawn without waiting for the
+    // job's next run
+    pub fn set_text(&self, text: &str) -> bool {
+        self.update(|job_queue, id| match job_queue.get_mut(id) {
+            Some(job) => {
+                job.text = unidecode(text);
+                job.content = None;
+                job.fetching = false;
+                job.fetched = false;
+                job.scroll.reset();
+                job.has_run = false;
+                job_queue.wake(id);
+                true
+            }
+            None => false,
+        })
+    }
+
+    pub fn set_rate(&self, rate: Option<Duration>) -> bool {
+        self.update(|job_queue, id| job_queue.set_rate(id, rate))
+    }
+
+    pub fn pause(&self) -> bool {
+        self.update(|job_queue, id| job_queue.pause(id))
+    }
+
+    pub fn resume(&self) -> bool {
+        self.update(|job_queue, id| job_queue.resume(id))
+    }
+
+    pub fn cancel(&self) -> bool {
+        self.update(|job_queue, id| job_queue.remove(id).is_some())
+    }
+
+    // Deadlines may have changed, so wake the worker up to look again
+    fn update<F: FnOnce(&mut JobQueue, JobId) -> bool>(&self, f: F) -> bool {
+        let updated = f(&mut self.job_queue.lock(), self.id);
+        self.signal.notify();
+        updated
+    }
+}
+
+impl<C: Clock> JobQueue<C> {
+    pub fn new(clock: C) -> Self {
+        JobQueue {
+            clock,
+            deadlines: BinaryHeap::new(),
+            jobs: HashMap::new(),
+            paused: HashMap::new(),
+            parked: HashMap::new(),
+            pages: Vec::new(),
+            active_page: None,
+            page_switch: None,
+            rotation: None,
+            next_rotation: None,
+            transition: Transition::default(),
+            notifications: BinaryHeap::new(),
+            notification: None,
+            next_id: 0,
+            next_seq: 0,
+        }
+    }
+
+    pub fn now(&self) -> Instant {
+        self.clock.now()
+    }
+
+    // Includes paused jobs and jobs on pages that aren't showing
+    pub fn len(&self) -> usize {
+        self.jobs.len() + self.paused.len() + self.parked.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn contains(&self, id: JobId) -> bool {
+        self.jobs.contains_key(&id)
+            || self.paused.contains_key(&id)
+            || self.parked.contains_key(&id)
+    }
+
+    pub fn get(&self, id: JobId) -> Option<&Job> {
+        self.jobs
+            .get(&id)
+            .or_else(|| self.paused.get(&id))
+            .or_else(|| self.parked.get(&id))
+    }
+
+    pub fn get_mut(&mut self, id: JobId) -> Option<&mut Job> {
+        if self.jobs.contains_key(&id) {
+            self.jobs.get_mut(&id)
+        } else if self.paused.contains_key(&id) {
+            self.paused.get_mut(&id)
         } else {
-            self.text.clone().as_str()
-                [self.index as usize..(self.index + driver.get_cols() as i32) as usize]
-                .to_string()
+            self.parked.get_mut(&id)
+        }
+    }
+
+    pub fn remove(&mut self, id: JobId) -> Option<Job> {
+        self.jobs
+            .remove(&id)
+            .or_else(|| self.paused.remove(&id))
+            .or_else(|| self.parked.remove(&id))
+    }
+
+    pub fn pause(&mut self, id: JobId) -> bool {
+        match self.jobs.remove(&id).or_else(|| self.parked.remove(&id)) {
+            Some(job) => {
+                self.paused.insert(id, job);
+                true
+            }
+            None => self.paused.contains_key(&id),
+        }
+    }
+
+    // Resumed jobs are due straight away if their page is showing
+    pub fn resume(&mut self, id: JobId) -> bool {
+        match self.paused.remove(&id) {
+            Some(job) => {
+                self.park_or_schedule(job);
+                true
+            }
+            None => self.contains(id),
+        }
+    }
+
+    // The new rate takes effect from now and replaces any schedule, a paused job picks it up when
+    // it is resumed
+    pub fn set_rate(&mut self, id: JobId, rate: Option<Duration>) -> bool {
+        if !self.jobs.contains_key(&id) {
+            return match self.get_mut(id) {
+                Some(job) => {
+                    job.rate = rate;
+                    job.schedule = None;
+                    self.wake(id);
+                    true
+                }
+                None => false,
+            };
+        }
+        match self.jobs.remove(&id) {
+            Some(mut job) => {
+                job.rate = rate;
+                job.schedule = None;
+                let now = self.now();
+                self.schedule(job, now);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Make a job run again straight away after it changed, rather than at its next run or the
+    // next time its page is shown. Only paused jobs and jobs on hidden pages are left waiting
+    pub fn wake(&mut self, id: JobId) {
+        if let Some(job) = self.jobs.remove(&id) {
+            let now = self.now();
+            self.schedule(job, now);
+            return;
+        }
+        let visible = match self.parked.get(&id) {
+            Some(job) => self.is_visible(job),
+            None => false,
+        };
+        if visible {
+            let job = self.parked.remove(&id).unwrap();
+            let now = self.now();
+            self.schedule(job, now);
+        }
+    }
+
+    // Runs the showing jobs on `row` again, returning the spans of the row none of them cover
+    pub fn redraw_row(&mut self, row: u8, cols: u8) -> Vec<(u8, u8)> {
+        let ids: Vec<JobId> = self
+            .jobs
+            .values()
+            .chain(self.parked.values())
+            .filter(|job| job.region.row == row)
+            .map(|job| job.id)
+            .collect();
+        for id in ids {
+            self.wake(id);
+        }
+        let covering: Vec<Region> = self
+            .jobs
+            .values()
+            .chain(self.paused.values())
+            .chain(self.parked.values())
+            .filter(|job| self.is_visible(job) && job.region.row == row)
+            .map(|job| job.region)
+            .collect();
+        Region::row(row).visible_spans(&covering, cols)
+    }
+
+    // New jobs are due straight away
+    pub fn push(&mut self, mut job: Job) -> JobId {
+        self.assign_id(&mut job);
+        let id = job.id;
+        let now = self.now();
+        self.schedule(job, now);
+        id
+    }
+
+    pub fn add_page(&mut self, page: Page) -> PageId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.pages.push(id);
+        for mut job in page.into_jobs() {
+            self.assign_id(&mut job);
+            job.page = Some(id);
+            self.parked.insert(job.id, job);
+        }
+        if self.active_page.is_none() && self.page_switch.is_none() {
+            self.page_switch = Some(Some(id));
+        }
+        id
+    }
+
+    pub fn push_to_page(&mut self, page: PageId, mut job: Job) -> Option<JobId> {
+        if !self.pages.contains(&page) {
+            return None;
+        }
+        self.assign_id(&mut job);
+        job.page = Some(page);
+        let id = job.id;
+        self.park_or_schedule(job);
+        Some(id)
+    }
+
+    // Removing the page being shown moves on to the next one
+    pub fn remove_page(&mut self, page: PageId) -> bool {
+        let position = match self.pages.iter().position(|id| *id == page) {
+            Some(position) => position,
+            None => return false,
+        };
+        self.pages.remove(position);
+        if self.active_page == Some(page) {
+            let next = self.pages.get(position % self.pages.len().max(1)).copied();
+            self.page_switch = Some(next);
+        } else if self.page_switch == Some(Some(page)) {
+            self.page_switch = None;
+        }
+        self.retain(|job| job.page != Some(page));
+        true
+    }
+
+    pub fn show_page(&mut self, page: PageId) -> bool {
+        if self.pages.contains(&page) {
+            self.page_switch = Some(Some(page));
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn next_page(&mut self) {
+        let current = match self.page_switch {
+            Some(page) => page,
+            None => self.active_page,
+        };
+        let next = match current.and_then(|page| self.pages.iter().position(|id| *id == page)) {
+            Some(position) => self.pages.get((position + 1) % self.pages.len()).copied(),
+            None => self.pages.first().copied(),
+        };
+        if next != self.active_page || self.page_switch.is_some() {
+            self.page_switch = Some(next);
+        }
+    }
+
+    pub fn active_page(&self) -> Option<PageId> {
+        self.active_page
+    }
+
+    pub fn transition(&self) -> Transition {
+        self.transition
+    }
+
+    pub fn set_transition(&mut self, transition: Transition) {
+        self.transition = transition;
+    }
+
+    pub fn set_rotation(&mut self, interval: Option<Duration>) {
+        self.rotation = interval;
+        self.next_rotation = interval.map(|interval| self.now() + interval);
+    }
+
+    // A page change that has been asked for or is due from rotation
+    pub fn take_page_switch(&mut self) -> Option<Option<PageId>> {
+        if self.notification.is_some() {
+            return None;
+        }
+        if let Some(next_rotation) = self.next_rotation {
+            if next_rotation <= self.now() && self.page_switch.is_none() {
+                self.next_page();
+                self.next_rotation = self.rotation.map(|interval| next_rotation + interval);
+            }
+        }
+        self.page_switch.take()
+    }
+
+    // Park the jobs of the page being shown and bring back the ones for `page`
+    pub fn show_page_now(&mut self, page: Option<PageId>) {
+        let old_page = self.active_page;
+        self.active_page = page;
+        let hidden: Vec<JobId> = self
+            .jobs
+            .values()
+            .filter(|job| job.page.is_some() && job.page == old_page)
+            .map(|job| job.id)
+            .collect();
+        for id in hidden {
+            let job = self.jobs.remove(&id).unwrap();
+            self.parked.insert(id, job);
+        }
+        let shown: Vec<JobId> = self
+            .parked
+            .values()
+            .filter(|job| job.page.is_some() && job.page == page)
+            .map(|job| job.id)
+            .collect();
+        for id in shown {
+            self.wake(id);
+        }
+        if let Some(rotation) = self.rotation {
+            self.next_rotation = Some(self.now() + rotation);
+        }
+    }
+
+    pub fn notify(&mut self, notification: Notification) -> NotificationId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.notifications.push(PendingNotification {
+            id,
+            seq: self.next_seq,
+            notification,
+        });
+        self.next_seq += 1;
+        id
+    }
+
+    // Works for notifications that are showing or still waiting
+    pub fn dismiss(&mut self, id: NotificationId) -> bool {
+        let now = self.now();
+        if let Some(active) = self.notification.as_mut() {
+            if active.id == id && active.until > now {
+                active.until = now;
+                return true;
+            }
+        }
+        let count = self.notifications.len();
+        self.notifications.retain(|pending| pending.id != id);
+        self.notifications.len() != count
+    }
+
+    pub fn notification_showing(&self) -> bool {
+        self.notification.is_some()
+    }
+
+    // The screen to draw if a notification has started, blinked or ended. Once the last one ends
+    // the jobs underneath are drawn back as they were
+    pub fn notification_update(&mut self, cols: u8, rows: u8) -> Option<Vec<String>> {
+        let now = self.now();
+        let mut expired = false;
+        if let Some(active) = self.notification.as_mut() {
+            if active.until > now {
+                return match active.next_blink {
+                    Some(next_blink) if next_blink <= now => {
+                        active.visible = !active.visible;
+                        active.next_blink = active.notification.blink().map(|blink| now + blink);
+                        Some(active.notification.screen(cols, rows, active.visible))
+                    }
+                    _ => None,
+                };
+            }
+            self.notification = None;
+            expired = true;
+        }
+        match self.notifications.pop() {
+            Some(PendingNotification {
+                id, notification, ..
+            }) => {
+                let screen = notification.screen(cols, rows, true);
+                self.notification = Some(ActiveNotification {
+                    id,
+                    until: now + notification.duration(),
+                    visible: true,
+                    next_blink: notification.blink().map(|blink| now + blink),
+                    notification,
+                });
+                Some(screen)
+            }
+            None if expired => Some(self.compose(cols, rows)),
+            None => None,
+        }
+    }
+
+    // What the display looks like with the jobs that are showing, one string per row
+    pub fn compose(&self, cols: u8, rows: u8) -> Vec<String> {
+        let mut screen = vec![vec![b' '; cols as usize]; rows as usize];
+        let active_page = self.active_page;
+        let mut visible: Vec<&Job> = self
+            .jobs
+            .values()
+            .chain(self.paused.values())
+            .chain(self.parked.values())
+            .filter(|job| job.page.is_none() || job.page == active_page)
+            .collect();
+        visible.sort_by_key(|job| (job.layer, job.id));
+        for job in visible {
+            if let Some(line) = screen.get_mut(job.region.row as usize) {
+                let (start, end) = job.region.span(cols);
+                line[start as usize..end as usize]
+                    .copy_from_slice(job.preview(end - start).as_bytes());
+            }
+        }
+        screen
+            .into_iter()
+            .map(|line| String::from_utf8_lossy(&line).into_owned())
+            .collect()
+    }
+
+    // What should be on the display right now, the notification if one is showing
+    pub fn screen(&self, cols: u8, rows: u8) -> Vec<String> {
+        match self.notification.as_ref() {
+            Some(active) => active.notification.screen(cols, rows, active.visible),
+            None => self.compose(cols, rows),
+        }
+    }
+
+    pub fn next_deadline(&mut self) -> Option<Instant> {
+        self.drop_stale();
+        if let Some(active) = self.notification.as_ref() {
+            return Some(match active.next_blink {
+                Some(next_blink) => next_blink.min(active.until),
+                None => active.until,
+            });
+        }
+        if self.page_switch.is_some() || !self.notifications.is_empty() {
+            return Some(self.now());
+        }
+        let next_job = self.deadlines.peek().map(|Reverse(deadline)| deadline.at);
+        match (next_job, self.next_rotation) {
+            (Some(next_job), Some(next_rotation)) => Some(next_job.min(next_rotation)),
+            (next_job, next_rotation) => next_job.or(next_rotation),
+        }
+    }
+
+    pub fn time_until_next(&mut self) -> Option<Duration> {
+        let now = self.now();
+        self.next_deadline()
+            .map(|at| at.saturating_duration_since(now))
+    }
+
+    // Take the next job if it is due, it's handed back with `reschedule` once it has run
+    pub fn pop_due(&mut self) -> Option<Job> {
+        let id = self.due_id()?;
+        self.deadlines.pop();
+        self.jobs.remove(&id)
+    }
+
+    // The `content` of the next job due if it's dynamic and hasn't been fetched for this run. It
+    // can then be called without the queue locked, and is handed back with `put_content`
+    pub fn take_due_content(&mut self) -> Option<(JobId, ContentFn)> {
+        let id = self.due_id()?;
+        let job = self.jobs.get_mut(&id)?;
+        if job.fetched {
+            return None;
+        }
+        let content = job.content.take()?;
+        job.fetching = true;
+        Some((id, content))
+    }
+
+    // The text is dropped if the job was given static text or removed in the meantime
+    pub fn put_content(&mut self, id: JobId, content: ContentFn, text: String) {
+        if let Some(job) = self.get_mut(id) {
+            if job.fetching {
+                job.content = Some(content);
+                job.text = unidecode(&text);
+                job.fetching = false;
+                job.fetched = true;
+            }
+        }
+    }
+
+    fn due_id(&mut self) -> Option<JobId> {
+        if self.notification.is_some() {
+            return None;
+        }
+        self.drop_stale();
+        let now = self.now();
+        match self.deadlines.peek() {
+            Some(Reverse(deadline)) if deadline.at <= now => Some(deadline.id),
+            _ => None,
+        }
+    }
+
+    // Periodic jobs are put back one period after their last deadline so they don't drift, or
+    // one period from now if they have fallen behind. One off jobs are parked, so they can be
+    // changed later and pages can show them again next time around
+    pub fn reschedule(&mut self, mut job: Job) {
+        job.has_run = true;
+        if let Some(runs_left) = job.runs_left.as_mut() {
+            *runs_left = runs_left.saturating_sub(1);
+            if *runs_left == 0 {
+                return;
+            }
+        }
+        let now = self.now();
+        let next_run = match (&job.schedule, job.rate) {
+            (Some(schedule), _) => schedule
+                .next_after(self.clock.local_now())
+                .map(|next_run| self.instant_at(next_run)),
+            (None, Some(rate)) => Some(match job.next_run {
+                Some(last_run) if last_run + rate >= now => last_run + rate,
+                _ => now + rate,
+            }),
+            (None, None) => None,
         };
-        driver.print(formatted_string.as_str()).unwrap();
-        self.index += 1;
-        if self.index > self.text.len() as i32 {
-            self.index = -((driver.get_cols() / 2) as i32);
+        match next_run {
+            Some(next_run) => self.schedule(job, next_run),
+            None if !job.is_periodic() => {
+                self.parked.insert(job.id, job);
+                self.drop_hidden();
+            }
+            None => {}
+        }
+    }
+
+    // Regions of the showing jobs that are drawn over `job`, paused jobs still keep their place
+    // on screen
+    pub fn covering(&self, job: &Job) -> Vec<Region> {
+        self.jobs
+            .values()
+            .chain(self.paused.values())
+            .chain(self.parked.values())
+            .filter(|other| {
+                self.is_visible(other) && other.region.row == job.region.row && job.is_below(other)
+            })
+            .map(|other| other.region)
+            .collect()
+    }
+
+    // Adding one off jobs over and over to update a row shouldn't pile them up, so once later
+    // jobs cover every cell of one that has run it goes. That leaves at most one for each cell
+    fn drop_hidden(&mut self) {
+        let hidden: Vec<JobId> = self
+            .parked
+            .values()
+            .filter(|job| !job.is_periodic() && self.is_hidden(job))
+            .map(|job| job.id)
+            .collect();
+        for id in hidden {
+            self.parked.remove(&id);
+        }
+    }
+
+    // Whether the jobs drawn over `job` cover all of it, whatever size the display is. Jobs that
+    // aren't on a page show on all of them
+    fn is_hidden(&self, job: &Job) -> bool {
+        let covering: Vec<Region> = self
+            .jobs
+            .values()
+            .chain(self.paused.values())
+            .chain(self.parked.values())
+            .filter(|other| (other.page.is_none() || other.page == job.page) && job.is_below(other))
+            .map(|other| other.region)
+            .collect();
+        job.region.visible_spans(&covering, u8::MAX).is_empty()
+    }
+
+    pub fn retain<F: FnMut(&Job) -> bool>(&mut self, mut f: F) {
+        self.jobs.retain(|_, job| f(job));
+        self.paused.retain(|_, job| f(job));
+        self.parked.retain(|_, job| f(job));
+    }
+
+    // Pages are removed along with their jobs
+    pub fn clear(&mut self) {
+        self.jobs.clear();
+        self.paused.clear();
+        self.parked.clear();
+        self.deadlines.clear();
+        self.pages.clear();
+        if self.active_page.is_some() {
+            self.page_switch = Some(None);
+        }
+    }
+
+    fn assign_id(&mut self, job: &mut Job) {
+        job.id = self.next_id;
+        self.next_id += 1;
+    }
+
+    fn is_visible(&self, job: &Job) -> bool {
+        job.page.is_none() || job.page == self.active_page
+    }
+
+    // Put back a job that failed to draw, it runs again as soon as the worker has backed off
+    pub fn retry(&mut self, job: Job) {
+        self.park_or_schedule(job);
+    }
+
+    // The same as `retry` for a job that was put back before it was drawn, paused jobs stay paused
+    pub fn retry_id(&mut self, id: JobId) {
+        if let Some(job) = self.jobs.remove(&id).or_else(|| self.parked.remove(&id)) {
+            self.retry(job);
+        }
+    }
+
+    fn park_or_schedule(&mut self, job: Job) {
+        if self.is_visible(&job) {
+            let now = self.now();
+            self.schedule(job, now);
+        } else {
+            self.parked.insert(job.id, job);
+        }
+    }
+
+    // Converts a local time to when it will be on the monotonic clock, times already gone are now
+    fn instant_at(&self, local: DateTime<Local>) -> Instant {
+        let until = (local - self.clock.local_now())
+            .to_std()
+            .unwrap_or_default();
+        self.now() + until
+    }
+
+    // Jobs that haven't reached their start time wait for it, jobs past their stop time or out of
+    // runs are dropped
+    fn schedule(&mut self, mut job: Job, mut at: Instant) {
+        if job.runs_left == Some(0) {
+            return;
+        }
+        if let Some(start_at) = job.start_at {
+            at = at.max(self.instant_at(start_at));
+        }
+        if let Some(stop_at) = job.stop_at {
+            if at > self.instant_at(stop_at) {
+                return;
+            }
+        }
+        job.seq = self.next_seq;
+        self.next_seq += 1;
+        job.next_run = Some(at);
+        self.deadlines.push(Reverse(Deadline {
+            at,
+            periodic: job.is_periodic(),
+            seq: job.seq,
+            id: job.id,
+        }));
+        self.jobs.insert(job.id, job);
+    }
+
+    fn drop_stale(&mut self) {
+        while let Some(Reverse(deadline)) = self.deadlines.peek() {
+            match self.jobs.get(&deadline.id) {
+                Some(job) if job.seq == deadline.seq => break,
+                _ => {
+                    self.deadlines.pop();
+                }
+            }
         }
     }
 }
 
-impl Eq for Job {}
+impl Eq for PendingNotification {}
 
-impl PartialEq for Job {
+impl PartialEq for PendingNotification {
     fn eq(&self, other: &Self) -> bool {
-        self.last_run.eq(&other.last_run)
+        self.cmp(other) == Ordering::Equal
     }
 }
 
-/*
-(0. Jobs that should have already been run) Might want to look into making this happen...
-1. One off jobs
-2. Un run scheduled jobs
-3. Next job to be run on schedule
-*/
-impl Ord for Job {
+// Highest priority first, then oldest first
+impl Ord for PendingNotification {
     fn cmp(&self, other: &Self) -> Ordering {
-        match self.rate {
-            Some(rate) => match other.rate {
-                Some(other_rate) => match self.last_run {
-                    Some(last_run) => match other.last_run {
-                        Some(other_last_run) => {
-                            let now = Instant::now();
-                            let self_dif = rate.checked_sub(now - last_run);
-                            let other_dif = other_rate.checked_sub(now - other_last_run);
-                            match self_dif {
-                                Some(self_dif) => match other_dif {
-                                    Some(other_dif) => self_dif.cmp(&other_dif),
-                                    None => Ordering::Greater,
-                                },
-                                None => match other_dif {
-                                    Some(other_dif) => Ordering::Less,
-                                    None => Ordering::Equal,
-                                },
-                            }
-                        }
-                        None => Ordering::Greater,
-                    },
-                    None => match other.last_run {
-                        Some(_) => Ordering::Less,
-                        None => rate.cmp(&other_rate),
-                    },
-                },
-                None => Ordering::Greater,
-            },
-            None => match other.rate {
-                Some(other_rate) => Ordering::Less,
-                None => Ordering::Equal,
-            },
-        }
+        (self.notification.priority(), Reverse(self.seq))
+            .cmp(&(other.notification.priority(), Reverse(other.seq)))
     }
 }
 
-impl PartialOrd for Job {
+impl PartialOrd for PendingNotification {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
+pub(crate) fn draw_screen(lcd: &LcdDriver, screen: &[String]) -> Result<(), Error> {
+    for (row, line) in screen.iter().enumerate() {
+        lcd.set_cursor(row as u8, 0)?;
+        lcd.print(line)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn draw_spans(lcd: &LcdDriver, row: u8, spans: &[(u8, String)]) -> Result<(), Error> {
+    for (col, text) in spans {
+        lcd.set_cursor(row, *col)?;
+        lcd.print(text)?;
+    }
+    Ok(())
+}
+
+impl Job {
+    pub fn new(text: &str, row: u8, rate: Option<Duration>) -> Self {
+        Job {
+            text: unidecode(text),
+            content: None,
+            region: Region::row(row),
+            layer: 0,
+            scroll: Scroller::default(),
+            rate,
+            schedule: None,
+            start_at: None,
+            stop_at: None,
+            runs_left: None,
+            id: 0,
+            page: None,
+            seq: 0,
+            next_run: None,
+            has_run: false,
+            fetching: false,
+            fetched: false,
+        }
+    }
+
+    pub fn empty(row: u8) -> Self {
+        Job::new("", row, None)
+    }
+
+    // The text is fetched from `content` each time the job runs, then padded and scrolled the same
+    // way as a static job
+    pub fn dynamic<F>(row: u8, rate: Option<Duration>, content: F) -> Self
+    where
+        F: FnMut() -> String + Send + 'static,
+    {
+        let mut job = Job::new("", row, rate);
+        job.content = Some(Box::new(content));
+        job
+    }
+
+    pub fn dynamic_segments<F>(row: u8, rate: Option<Duration>, mut content: F) -> Self
+    where
+        F: FnMut() -> Vec<Segment> + Send + 'static,
+    {
+        Job::dynamic(row, rate, move || {
+            content()
+                .into_iter()
+                .map(|segment| segment.to_text())
+                .collect()
+        })
+    }
+
+    pub fn with_region(mut self, region: Region) -> Self {
+        self.region = region;
+        self
+    }
+
+    pub fn with_layer(mut self, layer: i32) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    pub fn region(&self) -> Region {
+        self.region
+    }
+
+    pub fn with_scroll(mut self, scroll: Scroller) -> Self {
+        self.scroll = scroll;
+        self
+    }
+
+    // The job still runs as soon as it is added (or its page is shown), then follows the schedule
+    pub fn with_schedule<S: Into<Schedule>>(mut self, schedule: S) -> Self {
+        self.schedule = Some(schedule.into());
+        self
+    }
+
+    pub fn starting_at(mut self, start_at: DateTime<Local>) -> Self {
+        self.start_at = Some(start_at);
+        self
+    }
+
+    pub fn stopping_at(mut self, stop_at: DateTime<Local>) -> Self {
+        self.stop_at = Some(stop_at);
+        self
+    }
+
+    pub fn times(mut self, runs: u32) -> Self {
+        self.runs_left = Some(runs);
+        self
+    }
+
+    fn is_periodic(&self) -> bool {
+        self.rate.is_some() || self.schedule.is_some()
+    }
+
+    // Pull new text from a dynamic job, scrolling carries on from the same position
+    fn refresh(&mut self) {
+        if let Some(content) = self.content.as_mut() {
+            self.text = unidecode(&content());
+        }
+    }
+
+    pub fn id(&self) -> JobId {
+        self.id
+    }
+
+    pub fn page(&self) -> Option<PageId> {
+        self.page
+    }
+
+    // The frame the job would draw next, without moving its scroll position along. Dynamic jobs
+    // show the text from their last run, `content` is only called when the job runs
+    fn preview(&self, width: u8) -> String {
+        self.scroll.clone().frame(&self.text, width as usize)
+    }
+
+    pub fn run(&mut self, driver: Arc<Mutex<LcdDriver>>) -> Result<(), Error> {
+        self.run_visible(driver, &[])
+    }
+
+    // Draw the job's region, leaving alone any cells inside `covering`
+    pub fn run_visible(
+        &mut self,
+        driver: Arc<Mutex<LcdDriver>>,
+        covering: &[Region],
+    ) -> Result<(), Error> {
+        let cols = driver.lock().get_cols();
+        self.refresh();
+        let spans = self.render(cols, covering);
+        draw_spans(&driver.lock(), self.region.row, &spans)
+    }
+
+    // Moves the job on to its next frame and returns the parts of it to draw, as the column each
+    // starts at and its text. Doesn't touch the display or call `content`, so it can be done with
+    // the queue locked. Dynamic jobs show what was last fetched with `take_due_content`
+    pub(crate) fn render(&mut self, cols: u8, covering: &[Region]) -> Vec<(u8, String)> {
+        self.fetched = false;
+        let frame = self
+            .scroll
+            .frame(&self.text, self.region.width_on(cols) as usize);
+        let (start, _) = self.region.span(cols);
+        self.region
+            .visible_spans(covering, cols)
+            .into_iter()
+            .map(|(span_start, span_end)| {
+                let text = &frame[(span_start - start) as usize..(span_end - start) as usize];
+                (span_start, text.to_string())
+            })
+            .collect()
+    }
+
+    // Whether `other` is drawn over this job
+    fn is_below(&self, other: &Job) -> bool {
+        (self.layer, self.id) < (other.layer, other.id)
+    }
+}
+
+impl fmt::Debug for Job {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Job")
+            .field("text", &self.text)
+            .field("dynamic", &self.content.is_some())
+            .field("region", &self.region)
+            .field("layer", &self.layer)
+            .field("scroll", &self.scroll)
+            .field("rate", &self.rate)
+            .field("schedule", &self.schedule)
+            .field("start_at", &self.start_at)
+            .field("stop_at", &self.stop_at)
+            .field("runs_left", &self.runs_left)
+            .field("id", &self.id)
+            .field("page", &self.page)
+            .field("next_run", &self.next_run)
+            .field("has_run", &self.has_run)
+            .field("fetched", &self.fetched)
+            .finish()
+    }
+}
+
+impl Segment {
+    fn to_text(&self) -> String {
+        match self {
+            Segment::Text(text) => text.clone(),
+            Segment::Icon(icon) => icon.as_char().to_string(),
+        }
+    }
+}
+
+impl From<&str> for Segment {
+    fn from(text: &str) -> Self {
+        Segment::Text(text.to_string())
+    }
+}
+
+impl From<String> for Segment {
+    fn from(text: String) -> Self {
+        Segment::Text(text)
+    }
+}
+
+impl From<Icon> for Segment {
+    fn from(icon: Icon) -> Self {
+        Segment::Icon(icon)
+    }
+}
+
+#[cfg(test)]
 mod test {
+    use crate::clock::TestClock;
+    use crate::pages::Page;
+    use crate::schedule::Cron;
     use crate::scheduler::*;
-    use std::time::{Duration, Instant};
+    use crate::scroll::ScrollMode;
+    use crate::sim::Simulator;
+    use chrono::TimeZone;
+    use std::sync::atomic::AtomicUsize;
+    use std::time::Duration;
+
+    fn pop_all_due(queue: &mut JobQueue<TestClock>) -> Vec<JobId> {
+        let mut ids = Vec::new();
+        let mut ran = Vec::new();
+        while let Some(job) = queue.pop_due() {
+            ids.push(job.id());
+            ran.push(job);
+        }
+        for job in ran {
+            queue.reschedule(job);
+        }
+        ids
+    }
+
+    // Adds a job and runs everything that's due
+    fn add(queue: &mut JobQueue<TestClock>, job: Job) -> JobId {
+        let id = queue.push(job);
+        pop_all_due(queue);
+        id
+    }
+
+    #[test]
+    fn job_order_test() {
+        let clock = TestClock::new();
+        let mut queue = JobQueue::new(clock.clone());
+        let slow = queue.push(Job::new("", 0, Some(Duration::from_millis(250))));
+        let medium = queue.push(Job::new("", 0, Some(Duration::from_millis(150))));
+        let once = queue.push(Job::new("", 0, None));
+        let fast = queue.push(Job::new("", 0, Some(Duration::from_millis(100))));
+        let once_again = queue.push(Job::new("", 0, None));
+
+        // One off jobs first, then un run scheduled jobs in the order they were added
+        assert_eq!(
+            pop_all_due(&mut queue),
+            vec![once, once_again, slow, medium, fast]
+        );
+        // The first one off job is under the second, which replaces it
+        assert_eq!(queue.len(), 4);
+        assert!(!queue.contains(once));
+        assert_eq!(queue.time_until_next(), Some(Duration::from_millis(100)));
+
+        // Then the next job to be run on schedule
+        clock.advance(Duration::from_millis(150));
+        assert_eq!(pop_all_due(&mut queue), vec![fast, medium]);
+        clock.advance(Duration::from_millis(50));
+        assert_eq!(pop_all_due(&mut queue), vec![fast]);
+        clock.advance(Duration::from_millis(50));
+        assert_eq!(pop_all_due(&mut queue), vec![slow]);
+        // Equal deadlines go to whichever was rescheduled first
+        clock.advance(Duration::from_millis(50));
+        assert_eq!(pop_all_due(&mut queue), vec![medium, fast]);
+    }
+
+    #[test]
+    fn job_tie_test() {
+        let clock = TestClock::new();
+        let mut queue = JobQueue::new(clock.clone());
+        let first = queue.push(Job::new("", 0, Some(Duration::from_millis(100))));
+        let second = queue.push(Job::new("", 1, Some(Duration::from_millis(100))));
+        for _ in 0..5 {
+            assert_eq!(pop_all_due(&mut queue), vec![first, second]);
+            clock.advance(Duration::from_millis(100));
+        }
+    }
+
+    #[test]
+    fn job_update_test() {
+        let clock = TestClock::new();
+        let mut queue = JobQueue::new(clock.clone());
+        let job = queue.push(Job::new("", 0, Some(Duration::from_millis(100))));
+        let other = queue.push(Job::new("", 1, Some(Duration::from_millis(100))));
+        pop_all_due(&mut queue);
+
+        // Paused jobs keep their place in the queue but never come due
+        assert!(queue.pause(job));
+        assert!(queue.contains(job));
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(pop_all_due(&mut queue), vec![other]);
+        assert!(queue.resume(job));
+        assert_eq!(pop_all_due(&mut queue), vec![job]);
+
+        // Changing the rate reschedules from now
+        assert!(queue.set_rate(job, Some(Duration::from_millis(20))));
+        assert_eq!(pop_all_due(&mut queue), vec![job]);
+        clock.advance(Duration::from_millis(20));
+        assert_eq!(pop_all_due(&mut queue), vec![job]);
+
+        // A changed job doesn't wait for its next run to show the change
+        queue.wake(job);
+        assert_eq!(pop_all_due(&mut queue), vec![job]);
+
+        assert!(queue.remove(job).is_some());
+        assert!(!queue.contains(job));
+        assert!(!queue.pause(job));
+        assert!(!queue.resume(job));
+        assert!(!queue.set_rate(job, None));
+    }
+
+    #[test]
+    fn one_off_update_test() {
+        let job_queue = Arc::new(Mutex::new(JobQueue::new(SystemClock)));
+        let signal = Signal::Thread(Arc::new(Condvar::new()));
+        let id = job_queue.lock().push(Job::new("before", 0, None));
+        let handle = JobHandle::new(id, Arc::clone(&job_queue), signal);
+        assert!(!handle.has_run());
+        let job = job_queue.lock().pop_due().unwrap();
+        job_queue.lock().reschedule(job);
+        assert!(job_queue.lock().pop_due().is_none());
+
+        // A one off job that has run can still be changed, and runs again with the change
+        assert!(handle.is_active());
+        assert!(handle.has_run());
+        assert!(handle.set_text("after"));
+        assert!(!handle.has_run());
+        let job = job_queue.lock().pop_due().unwrap();
+        assert_eq!((job.id(), job.text.as_str()), (id, "after"));
+        job_queue.lock().reschedule(job);
+        assert_eq!(job_queue.lock().compose(6, 1), vec!["after "]);
+        assert!(handle.cancel());
+        assert!(!handle.set_text("gone"));
+    }
+
+    #[test]
+    fn one_off_prune_test() {
+        let mut queue = JobQueue::new(TestClock::new());
+        let title = add(&mut queue, Job::new("title", 0, None));
+        let status = add(&mut queue, Job::new("status", 1, None));
+        let left = add(
+            &mut queue,
+            Job::new("left", 0, None).with_region(Region::new(0, 0, 8)),
+        );
+        assert!(queue.contains(title));
+
+        // Gone once later jobs cover every cell between them, whichever regions they have
+        let right = add(
+            &mut queue,
+            Job::new("right", 0, None).with_region(Region::new(0, 8, 8)),
+        );
+        assert!(queue.contains(title));
+        let rest = add(
+            &mut queue,
+            Job::new("", 0, None).with_region(Region::new(0, 16, u8::MAX)),
+        );
+        assert!(!queue.contains(title));
+        assert!(queue.contains(left) && queue.contains(right) && queue.contains(rest));
+        assert!(queue.contains(status));
+
+        // Updating the same row over and over keeps one job for it
+        for update in 0..10 {
+            add(&mut queue, Job::new(&format!("update {}", update), 1, None));
+        }
+        assert!(!queue.contains(status));
+        assert_eq!(queue.len(), 4);
+        assert_eq!(
+            queue.compose(20, 2),
+            vec!["left    right       ", "update 9            "]
+        );
+    }
+
+    #[test]
+    fn redraw_row_test() {
+        let mut queue = JobQueue::new(TestClock::new());
+        // Periodic, so it isn't dropped while it's hidden
+        let left = add(
+            &mut queue,
+            Job::new("left", 0, Some(Duration::from_secs(60))).with_region(Region::new(0, 0, 4)),
+        );
+        let top = add(&mut queue, Job::new("top", 0, None));
+        add(&mut queue, Job::new("other", 1, None));
+        assert!(queue.remove(top).is_some());
+
+        // The job left on the row runs again and the rest of the row is blanked
+        assert_eq!(queue.redraw_row(0, 8), vec![(4, 8)]);
+        assert_eq!(queue.pop_due().map(|job| job.id()), Some(left));
+        assert!(queue.pop_due().is_none());
+        assert_eq!(queue.redraw_row(2, 8), vec![(0, 8)]);
+    }
+
+    #[test]
+    fn error_handler_test() {
+        let simulator = Simulator::new(8, 2);
+        let lcd = Arc::new(ThreadedLcd::with_driver(
+            LcdDriver::from_bus(simulator.clone(), 8, 2).unwrap(),
+        ));
+        lcd.set_error_policy(ErrorPolicy::Retry {
+            initial: Duration::from_millis(1),
+            max: Duration::from_millis(1),
+        });
+
+        // The handler can call back into the display it's reporting on
+        let handler_lcd = Arc::downgrade(&lcd);
+        let handler_simulator = simulator.clone();
+        lcd.on_error(move |_| {
+            if let Some(lcd) = handler_lcd.upgrade() {
+                assert_eq!(lcd.health().consecutive_errors, 1);
+                lcd.add_job(Job::new("error", 1, None));
+                handler_simulator.set_failing(false);
+            }
+        });
+        simulator.set_failing(true);
+        lcd.add_job(Job::new("hello", 0, None));
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while simulator.controller().lines('?') != vec!["hello   ", "error   "] {
+            assert!(Instant::now() < deadline, "the handler's job never ran");
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(lcd.health().errors, 1);
+        assert!(lcd.health().is_healthy());
+        match Arc::try_unwrap(lcd) {
+            Ok(lcd) => drop(lcd.shutdown(Shutdown::default())),
+            Err(_) => panic!("the handler kept hold of the display"),
+        }
+    }
+
+    #[test]
+    fn reinitialize_test() {
+        let simulator = Simulator::new(8, 2);
+        let driver = LcdDriver::from_bus(simulator.clone(), 8, 2).unwrap();
+        driver.create_char(0, [0x1F; 8]).unwrap();
+        let lcd = ThreadedLcd::with_driver(driver);
+        lcd.set_error_policy(ErrorPolicy::Reinitialize {
+            initial: Duration::from_millis(1),
+            max: Duration::from_millis(1),
+        });
+        let handler_simulator = simulator.clone();
+        lcd.on_error(move |_| handler_simulator.set_failing(false));
+        lcd.add_job(Job::new("hello", 0, None));
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while simulator.controller().lines('?')[0] != "hello   " {
+            assert!(Instant::now() < deadline, "the job never ran");
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        // The one off job on the first row has already run, it's drawn back after the display is
+        // reinitialized along with the character made before
+        simulator.set_failing(true);
+        lcd.add_job(Job::new("world", 1, None));
+        while simulator.controller().lines('?') != vec!["hello   ", "world   "] {
+            assert!(
+                Instant::now() < deadline,
+                "the display shows {:?}",
+                simulator.controller().lines('?')
+            );
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(lcd.health().errors, 1);
+        assert_eq!(simulator.controller().glyph(0), [0x1F; 8]);
+    }
+
+    #[test]
+    fn content_callback_test() {
+        let simulator = Simulator::new(8, 2);
+        let lcd = ThreadedLcd::with_driver(LcdDriver::from_bus(simulator.clone(), 8, 2).unwrap());
+        let status = lcd.add_job(Job::new("idle", 1, None));
+
+        // Content is fetched without the queue locked, so it can use the scheduler. Its own job
+        // is still in the queue while it does
+        let own: Arc<Mutex<Option<JobHandle>>> = Arc::new(Mutex::new(None));
+        let content_own = Arc::clone(&own);
+        let handle = lcd.add_job(Job::dynamic(0, Some(Duration::from_millis(5)), move || {
+            status.set_text("busy");
+            match content_own.lock().as_ref() {
+                Some(own) if own.is_active() => "active".to_string(),
+                Some(_) => "missing".to_string(),
+                None => "waiting".to_string(),
+            }
+        }));
+        *own.lock() = Some(handle.clone());
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while simulator.controller().lines('?') != vec!["active  ", "busy    "] {
+            assert!(
+                Instant::now() < deadline,
+                "the display shows {:?}",
+                simulator.controller().lines('?')
+            );
+            thread::sleep(Duration::from_millis(1));
+        }
+        // Drops the job's own handle, which the content holds on to
+        assert!(handle.cancel());
+        lcd.shutdown(Shutdown::default()).unwrap();
+    }
+
+    #[test]
+    fn dynamic_job_test() {
+        let mut count = 0;
+        let mut job = Job::dynamic(0, None, move || {
+            count += 1;
+            format!("Count {}", count)
+        });
+        job.refresh();
+        assert_eq!(job.text, "Count 1");
+        job.refresh();
+        assert_eq!(job.text, "Count 2");
+
+        let mut job = Job::dynamic_segments(0, None, || {
+            vec![Segment::from(Icon::MAIL), Segment::from(" 3 new")]
+        });
+        job.refresh();
+        assert_eq!(job.text, "\u{0} 3 new");
+
+        // Composing the screen shows the text from the last run without fetching it again
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counted = Arc::clone(&calls);
+        let mut queue = JobQueue::new(TestClock::new());
+        queue.push(Job::dynamic(0, None, move || {
+            counted.fetch_add(1, AtomicOrdering::SeqCst);
+            "live".to_string()
+        }));
+        assert_eq!(queue.compose(6, 1), vec!["      "]);
+        let mut job = queue.pop_due().unwrap();
+        job.refresh();
+        queue.reschedule(job);
+        assert_eq!(queue.compose(6, 1), vec!["live  "]);
+        assert_eq!(queue.compose(6, 1), vec!["live  "]);
+        assert_eq!(calls.load(AtomicOrdering::SeqCst), 1);
+
+        // The worker fetches content with the queue unlocked, static text set meanwhile wins
+        let job_queue = Arc::new(Mutex::new(JobQueue::new(SystemClock)));
+        let id = job_queue
+            .lock()
+            .push(Job::dynamic(0, None, || "fetched".to_string()));
+        let handle = JobHandle::new(id, Arc::clone(&job_queue), Signal::Thread(Arc::default()));
+        let (taken, mut content) = job_queue.lock().take_due_content().unwrap();
+        assert_eq!(taken, id);
+        assert!(job_queue.lock().take_due_content().is_none());
+        assert!(handle.is_active());
+        assert!(handle.set_text("static"));
+        let text = content();
+        job_queue.lock().put_content(id, content, text);
+        let mut job = job_queue.lock().pop_due().unwrap();
+        assert_eq!(job.render(8, &[]), vec![(0, "static  ".to_string())]);
+    }
+
+    #[test]
+    fn job_covering_test() {
+        let mut queue = JobQueue::new(TestClock::new());
+        queue.push(Job::new("title", 0, None).with_layer(1));
+        queue.push(Job::new("clock", 0, None).with_region(Region::new(0, 12, 4)));
+        queue.push(Job::new("status", 1, None));
+        let title = queue.pop_due().unwrap();
+        let clock = queue.pop_due().unwrap();
+        assert_eq!(queue.covering(&title), vec![]);
+        queue.push(title);
+        assert_eq!(queue.covering(&clock), vec![Region::row(0)]);
+    }
+
+    #[test]
+    fn page_test() {
+        let clock = TestClock::new();
+        let mut queue = JobQueue::new(clock.clone());
+        let status = queue.push(Job::new("", 1, Some(Duration::from_millis(100))));
+        let first_page = queue.add_page(Page::new().with_job(Job::new("first", 0, None)));
+        let second_page = queue.add_page(Page::new().with_job(Job::new("second", 0, None)));
+
+        // Nothing from a page runs until it is shown
+        assert_eq!(pop_all_due(&mut queue), vec![status]);
+        assert_eq!(queue.take_page_switch(), Some(Some(first_page)));
+        queue.show_page_now(Some(first_page));
+        assert_eq!(queue.compose(8, 2), vec!["first   ", "        "]);
+        assert_eq!(pop_all_due(&mut queue).len(), 1);
+        assert_eq!(queue.take_page_switch(), None);
+
+        // One off page jobs come back when their page is shown again
+        queue.set_rotation(Some(Duration::from_millis(1000)));
+        clock.advance(Duration::from_millis(1000));
+        assert_eq!(queue.take_page_switch(), Some(Some(second_page)));
+        queue.show_page_now(Some(second_page));
+        assert_eq!(queue.compose(8, 2), vec!["second  ", "        "]);
+        queue.next_page();
+        assert_eq!(queue.take_page_switch(), Some(Some(first_page)));
+        queue.show_page_now(Some(first_page));
+        assert_eq!(pop_all_due(&mut queue).len(), 2);
+
+        assert!(queue.remove_page(first_page));
+        assert_eq!(queue.take_page_switch(), Some(Some(second_page)));
+        queue.show_page_now(Some(second_page));
+        assert!(queue.remove_page(second_page));
+        assert_eq!(queue.take_page_switch(), Some(None));
+        assert_eq!(queue.len(), 1);
+    }
+
+    #[test]
+    fn notification_test() {
+        let clock = TestClock::new();
+        let mut queue = JobQueue::new(clock.clone());
+        let job = queue.push(
+            Job::new("scrolling text", 0, Some(Duration::from_millis(100)))
+                .with_scroll(Scroller::new(ScrollMode::Loop { gap: 0 })),
+        );
+        let mut ran = queue.pop_due().unwrap();
+        ran.scroll.frame(&ran.text, 8);
+        queue.reschedule(ran);
+
+        let low = queue.notify(Notification::new("low", Duration::from_millis(500)));
+        let high = queue.notify(
+            Notification::new("high", Duration::from_millis(500))
+                .with_priority(1)
+                .with_blink(Duration::from_millis(200)),
+        );
+        assert_eq!(
+            queue.notification_update(8, 1),
+            Some(vec!["  high  ".to_string()])
+        );
+
+        // Jobs are held back until the notifications are over
+        clock.advance(Duration::from_millis(200));
+        assert!(queue.pop_due().is_none());
+        assert_eq!(queue.time_until_next(), Some(Duration::from_millis(0)));
+        assert_eq!(
+            queue.notification_update(8, 1),
+            Some(vec!["        ".to_string()])
+        );
+        assert_eq!(queue.notification_update(8, 1), None);
+        assert!(queue.dismiss(high));
+        assert_eq!(
+            queue.notification_update(8, 1),
+            Some(vec!["  low   ".to_string()])
+        );
+        assert!(queue.dismiss(low));
+        assert!(!queue.dismiss(low));
+
+        // The job comes back where it left off
+        assert_eq!(
+            queue.notification_update(8, 1),
+            Some(vec!["crolling".to_string()])
+        );
+        assert_eq!(pop_all_due(&mut queue), vec![job]);
+    }
+
+    #[test]
+    fn job_retain_test() {
+        let clock = TestClock::new();
+        let mut queue = JobQueue::new(clock.clone());
+        queue.push(Job::new("", 0, Some(Duration::from_millis(100))));
+        let kept = queue.push(Job::new("", 1, Some(Duration::from_millis(200))));
+        pop_all_due(&mut queue);
+        queue.retain(|job| job.region.row != 0);
+        assert_eq!(queue.time_until_next(), Some(Duration::from_millis(200)));
+        clock.advance(Duration::from_millis(200));
+        assert_eq!(pop_all_due(&mut queue), vec![kept]);
+        queue.clear();
+        assert!(queue.is_empty());
+        assert_eq!(queue.time_until_next(), None);
+    }
 
     #[test]
-    fn job_sort_test() {
-        let now = Instant::now();
-
-        let mut ref_vec = vec![
-            Job {
-                text: "".to_string(),
-                row: 0,
-                index: 0,
-                rate: None,
-                last_run: None,
-            },
-            Job {
-                text: "".to_string(),
-                row: 0,
-                index: 0,
-                rate: Option::from(Duration::from_millis(100)),
-                last_run: None,
-            },
-            Job {
-                text: "".to_string(),
-                row: 0,
-                index: 0,
-                rate: Option::from(Duration::from_millis(150)),
-                last_run: Option::from(now - Duration::from_millis(111)),
-            },
-            Job {
-                text: "".to_string(),
-                row: 0,
-                index: 0,
-                rate: Option::from(Duration::from_millis(250)),
-                last_run: Option::from(now - Duration::from_millis(111)),
-            },
-            Job {
-                text: "".to_string(),
-                row: 0,
-                index: 0,
-                rate: Option::from(Duration::from_millis(250)),
-                last_run: Option::from(now - Duration::from_millis(100)),
-            },
-        ];
-
-        let mut test_vec = vec![
-            Job {
-                text: "".to_string(),
-                row: 0,
-                index: 0,
-                rate: Option::from(Duration::from_millis(250)),
-                last_run: Option::from(now - Duration::from_millis(111)),
-            },
-            Job {
-                text: "".to_string(),
-                row: 0,
-                index: 0,
-                rate: Option::from(Duration::from_millis(150)),
-                last_run: Option::from(now - Duration::from_millis(111)),
-            },
-            Job {
-                text: "".to_string(),
-                row: 0,
-                index: 0,
-                rate: Option::from(Duration::from_millis(100)),
-                last_run: None,
-            },
-            Job {
-                text: "".to_string(),
-                row: 0,
-                index: 0,
-                rate: Option::from(Duration::from_millis(250)),
-                last_run: Option::from(now - Duration::from_millis(100)),
-            },
-            Job {
-                text: "".to_string(),
-                row: 0,
-                index: 0,
-                rate: None,
-                last_run: None,
-            },
-        ];
-
-        test_vec.sort();
-        assert_eq!(ref_vec, test_vec)
+    fn schedule_test() {
+        let local = |hour, min, sec| Local.with_ymd_and_hms(2020, 6, 1, hour, min, sec).unwrap();
+        let clock = TestClock::at(local(8, 59, 50));
+        let mut queue = JobQueue::new(clock.clone());
+        let minute = queue.push(
+            Job::new("clock", 0, None).with_schedule(Schedule::Aligned(Duration::from_secs(60))),
+        );
+        let twice = queue.push(
+            Job::new("shift", 1, None)
+                .with_schedule(Cron::parse("*/15 * * * * *").unwrap())
+                .times(2),
+        );
+        let later = queue.push(Job::new("later", 2, None).starting_at(local(9, 0, 5)));
+        let stopped = queue.push(
+            Job::new("stopped", 3, Some(Duration::from_secs(20))).stopping_at(local(9, 0, 0)),
+        );
+        // Everything but the job waiting to start runs when it is added
+        assert_eq!(pop_all_due(&mut queue), vec![minute, twice, stopped]);
+        assert_eq!(queue.time_until_next(), Some(Duration::from_secs(10)));
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(pop_all_due(&mut queue), vec![minute, twice]);
+        // The cron job has used up its runs and the rate job would run after its stop time
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(pop_all_due(&mut queue), vec![later]);
+        // The one off job stays around after running, in case it's changed
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.time_until_next(), Some(Duration::from_secs(55)));
     }
 }
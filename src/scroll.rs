@@ -0,0 +1,259 @@
+// Works out what part of a line of text is visible each time a job runs. Text is expected to be
+// ASCII (jobs run it through unidecode first) so it can be sliced by byte
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScrollMode {
+    // Scroll left until the text is gone, then come back in from half way across
+    #[default]
+    Marquee,
+    // Scroll left forever, with `gap` blank cells between the end of the text and its start
+    Loop {
+        gap: usize,
+    },
+    // Same as `Loop`, but the text moves to the right
+    Reverse {
+        gap: usize,
+    },
+    // Scroll left until the end of the text is showing, then back to the start
+    Bounce,
+    // Scroll left until the end of the text is showing and stay there
+    Once,
+    // Don't scroll, cut the text off with an ellipsis
+    Truncate,
+}
+
+#[derive(Debug, Clone)]
+pub struct Scroller {
+    mode: ScrollMode,
+    // Extra runs to hold the text at the start and end positions
+    pause_start: u32,
+    pause_end: u32,
+    offset: i32,
+    backwards: bool,
+    held: u32,
+}
+
+const ELLIPSIS: &str = "...";
+
+impl Default for Scroller {
+    fn default() -> Self {
+        Scroller::new(ScrollMode::default())
+    }
+}
+
+impl Scroller {
+    pub fn new(mode: ScrollMode) -> Self {
+        Scroller {
+            mode,
+            pause_start: 0,
+            pause_end: 0,
+            offset: 0,
+            backwards: false,
+            held: 0,
+        }
+    }
+
+    // The start is when the first character is at the left edge, the end is when the last
+    // character is at the right edge
+    pub fn with_pause(mut self, start: u32, end: u32) -> Self {
+        self.pause_start = start;
+        self.pause_end = end;
+        self
+    }
+
+    pub fn mode(&self) -> ScrollMode {
+        self.mode
+    }
+
+    pub fn reset(&mut self) {
+        self.offset = 0;
+        self.backwards = false;
+        self.held = 0;
+    }
+
+    // Returns exactly `width` characters to show for `text` and moves on to the next position.
+    // Text that fits is left aligned and never moves
+    pub fn frame(&mut self, text: &str, width: usize) -> String {
+        let len = text.len();
+        if len <= width {
+            return format!("{: <width$}", text, width = width);
+        }
+        if self.mode == ScrollMode::Truncate {
+            return truncate(text, width);
+        }
+        // The text may have been swapped for a shorter one
+        if self.offset.unsigned_abs() as usize > len + self.gap() {
+            self.reset();
+        }
+        let frame = match self.mode {
+            ScrollMode::Marquee => marquee(text, width, self.offset),
+            ScrollMode::Loop { gap } | ScrollMode::Reverse { gap } => {
+                let cycle = format!("{: <width$}", text, width = len + gap);
+                (0..width)
+                    .map(|i| cycle.as_bytes()[(self.offset as usize + i) % cycle.len()] as char)
+                    .collect()
+            }
+            _ => {
+                let start = self.offset.clamp(0, (len - width) as i32) as usize;
+                text[start..start + width].to_string()
+            }
+        };
+        self.advance(len, width);
+        frame
+    }
+
+    fn gap(&self) -> usize {
+        match self.mode {
+            ScrollMode::Loop { gap } | ScrollMode::Reverse { gap } => gap,
+            _ => 0,
+        }
+    }
+
+    fn advance(&mut self, len: usize, width: usize) {
+        let end = (len - width) as i32;
+        let pause = if self.offset == 0 {
+            self.pause_start
+        } else if self.offset == end {
+            self.pause_end
+        } else {
+            0
+        };
+        if self.held < pause {
+            self.held += 1;
+            return;
+        }
+        self.held = 0;
+        match self.mode {
+            ScrollMode::Marquee => {
+                self.offset += 1;
+                if self.offset > len as i32 {
+                    self.offset = -((width / 2) as i32);
+                }
+            }
+            ScrollMode::Loop { gap } => {
+                self.offset = (self.offset + 1) % (len + gap) as i32;
+            }
+            ScrollMode::Reverse { gap } => {
+                let cycle = (len + gap) as i32;
+                self.offset = (self.offset - 1 + cycle) % cycle;
+            }
+            ScrollMode::Bounce => {
+                if self.offset >= end {
+                    self.backwards = true;
+                } else if self.offset <= 0 {
+                    self.backwards = false;
+                }
+                self.offset += if self.backwards { -1 } else { 1 };
+            }
+            ScrollMode::Once => self.offset = (self.offset + 1).min(end),
+            ScrollMode::Truncate => {}
+        }
+    }
+}
+
+// Scrolls the text off to the left, then back on from the right
+fn marquee(text: &str, width: usize, offset: i32) -> String {
+    let len = text.len();
+    if offset < 0 {
+        let slice = &text[0..(offset + width as i32) as usize];
+        format!("{: >width$}", slice, width = width)
+    } else if len < offset as usize + width {
+        format!("{: <width$}", &text[offset as usize..len], width = width)
+    } else {
+        text[offset as usize..offset as usize + width].to_string()
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if width <= ELLIPSIS.len() {
+        text[0..width].to_string()
+    } else {
+        format!("{}{}", &text[0..width - ELLIPSIS.len()], ELLIPSIS)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::scroll::*;
+
+    fn frames(scroller: &mut Scroller, text: &str, width: usize, count: usize) -> Vec<String> {
+        (0..count).map(|_| scroller.frame(text, width)).collect()
+    }
+
+    #[test]
+    fn short_text_test() {
+        let mut scroller = Scroller::new(ScrollMode::Loop { gap: 2 });
+        assert_eq!(frames(&mut scroller, "abc", 4, 2), vec!["abc ", "abc "]);
+    }
+
+    #[test]
+    fn marquee_test() {
+        let mut scroller = Scroller::default();
+        assert_eq!(
+            frames(&mut scroller, "abcdef", 4, 10),
+            vec!["abcd", "bcde", "cdef", "def ", "ef  ", "f   ", "    ", "  ab", " abc", "abcd"]
+        );
+    }
+
+    #[test]
+    fn loop_test() {
+        let mut scroller = Scroller::new(ScrollMode::Loop { gap: 1 });
+        assert_eq!(
+            frames(&mut scroller, "abcde", 3, 7),
+            vec!["abc", "bcd", "cde", "de ", "e a", " ab", "abc"]
+        );
+    }
+
+    #[test]
+    fn reverse_test() {
+        let mut scroller = Scroller::new(ScrollMode::Reverse { gap: 1 });
+        assert_eq!(
+            frames(&mut scroller, "abcde", 3, 4),
+            vec!["abc", " ab", "e a", "de "]
+        );
+    }
+
+    #[test]
+    fn bounce_test() {
+        let mut scroller = Scroller::new(ScrollMode::Bounce);
+        assert_eq!(
+            frames(&mut scroller, "abcde", 3, 6),
+            vec!["abc", "bcd", "cde", "bcd", "abc", "bcd"]
+        );
+    }
+
+    #[test]
+    fn once_test() {
+        let mut scroller = Scroller::new(ScrollMode::Once);
+        assert_eq!(
+            frames(&mut scroller, "abcde", 3, 5),
+            vec!["abc", "bcd", "cde", "cde", "cde"]
+        );
+    }
+
+    #[test]
+    fn pause_test() {
+        let mut scroller = Scroller::new(ScrollMode::Bounce).with_pause(1, 2);
+        assert_eq!(
+            frames(&mut scroller, "abcd", 3, 7),
+            vec!["abc", "abc", "bcd", "bcd", "bcd", "abc", "abc"]
+        );
+    }
+
+    #[test]
+    fn truncate_test() {
+        let mut scroller = Scroller::new(ScrollMode::Truncate);
+        assert_eq!(
+            frames(&mut scroller, "abcdefgh", 6, 2),
+            vec!["abc...", "abc..."]
+        );
+        assert_eq!(scroller.frame("abcdefgh", 2), "ab");
+    }
+
+    #[test]
+    fn shorter_text_test() {
+        let mut scroller = Scroller::new(ScrollMode::Loop { gap: 0 });
+        frames(&mut scroller, "abcdefghij", 2, 9);
+        assert_eq!(scroller.frame("abc", 2), "ab");
+    }
+}
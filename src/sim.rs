@@ -0,0 +1,448 @@
+use crate::font::Rom;
+use crate::hd44780::*;
+use gpio_cdev::errors::Error;
+use parking_lot::{Mutex, MutexGuard};
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+// The controller's side of the bus: DDRAM, CGRAM, the address counter and the display flags,
+// updated from the same transfers that go out over the GPIO lines
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Controller {
+    cols: u8,
+    rows: u8,
+    ddram: [u8; 0x80],
+    cgram: [u8; 64],
+    address: u8,
+    // The address counter points into CGRAM after a CGRAM address is set, until a DDRAM one is
+    in_cgram: bool,
+    interface: Interface,
+    two_line: bool,
+    increment: bool,
+    shift_on_write: bool,
+    display_on: bool,
+    cursor_on: bool,
+    blink_on: bool,
+    // How far the display has been shifted left
+    shift: u8,
+    rom: Rom,
+}
+
+// Puts transfers back together into bytes the way the controller does, for anything else that
+// watches the bus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Interface {
+    eight_bit: bool,
+    // The high nibble of a byte that's half way in on a 4 bit interface
+    high_nibble: Option<u8>,
+}
+
+// A display with no hardware behind it, usable as a bus for `LcdDriver::from_bus`. Clones share
+// the same controller, so keep one to look at what has been drawn
+#[derive(Debug, Clone)]
+pub struct Simulator {
+    controller: Arc<Mutex<Controller>>,
+    eight_bit_bus: bool,
+    failing: Arc<AtomicBool>,
+}
+
+// DDRAM holds 40 characters a line with two lines, or 80 with one
+const LINE_LENGTH: u8 = 40;
+
+impl Controller {
+    // In the state the controller powers up in, which is 8 bit mode with the display off
+    pub fn new(cols: u8, rows: u8) -> Self {
+        Controller {
+            cols,
+            rows,
+            ddram: [b' '; 0x80],
+            cgram: [0; 64],
+            address: 0,
+            in_cgram: false,
+            interface: Interface::new(),
+            two_line: false,
+            increment: true,
+            shift_on_write: false,
+            display_on: false,
+            cursor_on: false,
+            blink_on: false,
+            shift: 0,
+            rom: Rom::A00,
+        }
+    }
+
+    // One transfer off the bus, `bits` being on D4 to D7 for a 4 bit bus
+    pub fn write_bits(&mut self, bits: u8, data: bool, eight_bit_bus: bool) {
+        if let Some(byte) = self.interface.receive(bits, data, eight_bit_bus) {
+            self.execute(byte, data);
+        }
+    }
+
+    // A whole instruction or data byte
+    pub fn execute(&mut self, byte: u8, data: bool) {
+        if data {
+            self.write_data(byte);
+        } else if byte & LCD_SET_DDRAM_ADDR != 0 {
+            self.in_cgram = false;
+            self.address = byte & 0x7F;
+        } else if byte & LCD_SET_CGRAM_ADDR != 0 {
+            self.in_cgram = true;
+            self.address = byte & 0x3F;
+        } else if byte & LCD_FUNCTION_SET != 0 {
+            self.interface.function_set(byte);
+            self.two_line = byte & LCD_2LINE != 0;
+        } else if byte & LCD_CURSOR_SHIFT != 0 {
+            let right = byte & LCD_RIGHT != 0;
+            if byte & LCD_DISPLAY_MOVE != 0 {
+                self.shift_display(!right);
+            } else {
+                self.address = self.step_address(right);
+            }
+        } else if byte & LCD_DISPLAY_CONTROL != 0 {
+            self.display_on = byte & LCD_DISPLAY_ON != 0;
+            self.cursor_on = byte & LCD_CURSOR_ON != 0;
+            self.blink_on = byte & LCD_BLINK_ON != 0;
+        } else if byte & LCD_ENTRY_MODE_SET != 0 {
+            self.increment = byte & LCD_ENTRY_LEFT != 0;
+            self.shift_on_write = byte & 0x01 != 0;
+        } else if byte & LCD_RETURN_HOME != 0 {
+            self.home();
+        } else if byte & LCD_CLEAR_DISPLAY != 0 {
+            self.ddram = [b' '; 0x80];
+            self.increment = true;
+            self.home();
+        }
+    }
+
+    fn write_data(&mut self, byte: u8) {
+        if self.in_cgram {
+            self.cgram[self.address as usize] = byte & 0x1F;
+            self.address = if self.increment {
+                (self.address + 1) & 0x3F
+            } else {
+                self.address.wrapping_sub(1) & 0x3F
+            };
+        } else {
+            self.ddram[self.address as usize] = byte;
+            self.address = self.step_address(self.increment);
+            if self.shift_on_write {
+                self.shift_display(self.increment);
+            }
+        }
+    }
+
+    fn home(&mut self) {
+        self.in_cgram = false;
+        self.address = 0;
+        self.shift = 0;
+    }
+
+    // The next DDRAM address, which skips the gap between the two lines
+    fn step_address(&self, forward: bool) -> u8 {
+        let (line, offset) = self.split_address(self.address);
+        let length = self.line_length();
+        let offset = if forward {
+            offset + 1
+        } else {
+            offset + length * 2 - 1
+        };
+        match (self.two_line, offset % (length * 2) < length) {
+            (true, same_line) => {
+                let line = if same_line { line } else { line ^ 1 };
+                line * 0x40 + offset % length
+            }
+            (false, _) => offset % length,
+        }
+    }
+
+    fn split_address(&self, address: u8) -> (u8, u8) {
+        if self.two_line {
+            (address >> 6 & 1, (address & 0x3F).min(LINE_LENGTH - 1))
+        } else {
+            (0, address.min(LINE_LENGTH * 2 - 1))
+        }
+    }
+
+    fn line_length(&self) -> u8 {
+        if self.two_line {
+            LINE_LENGTH
+        } else {
+            LINE_LENGTH * 2
+        }
+    }
+
+    fn shift_display(&mut self, left: bool) {
+        let length = self.line_length();
+        self.shift = if left {
+            (self.shift + 1) % length
+        } else {
+            (self.shift + length - 1) % length
+        };
+    }
+
+    // The DDRAM address showing at a position, rows past the second carry on from the first two
+    fn address_at(&self, row: u8, col: u8) -> u8 {
+        let length = self.line_length();
+        let start = if row >= 2 { self.cols } else { 0 };
+        let offset = (start + col + self.shift) % length;
+        if self.two_line {
+            (row & 1) * 0x40 + offset
+        } else {
+            offset
+        }
+    }
+
+    pub fn get_cols(&self) -> u8 {
+        self.cols
+    }
+
+    pub fn get_rows(&self) -> u8 {
+        self.rows
+    }
+
+    pub fn is_display_on(&self) -> bool {
+        self.display_on
+    }
+
+    pub fn is_underlined(&self) -> bool {
+        self.cursor_on
+    }
+
+    pub fn is_blinking(&self) -> bool {
+        self.blink_on
+    }
+
+    // The character code showing at a position
+    pub fn code_at(&self, row: u8, col: u8) -> u8 {
+        self.ddram[self.address_at(row, col) as usize]
+    }
+
+    // Where the underline or blinking block is, if either is on and it's on screen
+    pub fn cursor(&self) -> Option<(u8, u8)> {
+        if !(self.cursor_on || self.blink_on) || self.in_cgram {
+            return None;
+        }
+        (0..self.rows)
+            .flat_map(|row| (0..self.cols).map(move |col| (row, col)))
+            .find(|&(row, col)| self.address_at(row, col) == self.address)
+    }
+
+    // The pixels for a character code, codes 0 to 15 come from CGRAM with 8 to 15 repeating the
+    // first eight
+    pub fn glyph(&self, code: u8) -> [u8; 8] {
+        if code < 0x10 {
+            let start = (code & 0x07) as usize * 8;
+            let mut glyph = [0; 8];
+            glyph.copy_from_slice(&self.cgram[start..start + 8]);
+            glyph
+        } else {
+            self.rom.glyph(code).unwrap_or([0; 8])
+        }
+    }
+
+    // What a cell shows, with the cursor drawn in. A blinking cursor is drawn in the half of the
+    // blink where every pixel is on
+    pub fn pixels(&self, row: u8, col: u8) -> [u8; 8] {
+        if !self.display_on {
+            return [0; 8];
+        }
+        let mut glyph = self.glyph(self.code_at(row, col));
+        if self.cursor() == Some((row, col)) {
+            if self.blink_on {
+                glyph = [0x1F; 8];
+            } else {
+                glyph[7] = 0x1F;
+            }
+        }
+        glyph
+    }
+
+    // What's on screen as text, with `replacement` for the CGRAM codes and anything the ROM has
+    // that Unicode doesn't. A display that's off shows nothing
+    pub fn lines(&self, replacement: char) -> Vec<String> {
+        (0..self.rows)
+            .map(|row| {
+                (0..self.cols)
+                    .map(|col| match self.display_on {
+                        true => self
+                            .rom
+                            .decode(self.code_at(row, col))
+                            .unwrap_or(replacement),
+                        false => ' ',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl Interface {
+    // In 8 bit mode, which is how the controller powers up
+    pub(crate) fn new() -> Self {
+        Interface {
+            eight_bit: true,
+            high_nibble: None,
+        }
+    }
+
+    // For watching a bus that may already be running, which means a controller that has been
+    // switched to the bus's width. A 4 bit init sequence still comes through fine as the first
+    // two nibbles make an 8 bit function set
+    pub(crate) fn settled(eight_bit_bus: bool) -> Self {
+        Interface {
+            eight_bit: eight_bit_bus,
+            high_nibble: None,
+        }
+    }
+
+    // The byte a transfer completes, if it does. Function sets are picked up by themselves
+    pub(crate) fn receive(&mut self, bits: u8, data: bool, eight_bit_bus: bool) -> Option<u8> {
+        let byte = if self.eight_bit {
+            // Until it's told otherwise the controller reads all eight lines, with D0 to D3
+            // left low on a 4 bit bus
+            if eight_bit_bus {
+                bits
+            } else {
+                bits << 4
+            }
+        } else {
+            match self.high_nibble.take() {
+                Some(high) => high << 4 | bits & 0x0F,
+                None => {
+                    self.high_nibble = Some(bits & 0x0F);
+                    return None;
+                }
+            }
+        };
+        if !data && byte & 0xE0 == LCD_FUNCTION_SET {
+            self.function_set(byte);
+        }
+        Some(byte)
+    }
+
+    fn function_set(&mut self, byte: u8) {
+        self.eight_bit = byte & LCD_8BITMODE != 0;
+        self.high_nibble = None;
+    }
+}
+
+impl Simulator {
+    pub fn new(cols: u8, rows: u8) -> Self {
+        Simulator {
+            controller: Arc::new(Mutex::new(Controller::new(cols, rows))),
+            eight_bit_bus: true,
+            failing: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    // Which character ROM the simulated controller has, A00 unless set
+    pub fn with_rom(self, rom: Rom) -> Self {
+        self.controller.lock().rom = rom;
+        self
+    }
+
+    // Send nibbles instead of bytes, to go through the same init sequence as a 4 bit display
+    pub fn four_bit(mut self) -> Self {
+        self.eight_bit_bus = false;
+        self
+    }
+
+    pub fn controller(&self) -> MutexGuard<'_, Controller> {
+        self.controller.lock()
+    }
+
+    // Every write fails while this is set, like a display that has come loose, for trying out
+    // error handling
+    pub fn set_failing(&self, failing: bool) {
+        self.failing.store(failing, Ordering::SeqCst);
+    }
+}
+
+impl Bus for Simulator {
+    type Error = Error;
+
+    fn is_eight_bit(&self) -> bool {
+        self.eight_bit_bus
+    }
+
+    fn write_bits(&mut self, bits: u8, data: bool) -> Result<(), Error> {
+        if self.failing.load(Ordering::SeqCst) {
+            return Err(Error::from(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "Simulated bus error",
+            )));
+        }
+        self.controller
+            .lock()
+            .write_bits(bits, data, self.eight_bit_bus);
+        Ok(())
+    }
+
+    // Nothing to wait for, the simulated controller is never busy
+    fn delay_us(&mut self, _us: u32) {}
+}
+
+#[cfg(test)]
+mod test {
+    use crate::font::Rom;
+    use crate::icons::Icon;
+    use crate::lcd::LcdDriver;
+    use crate::sim::*;
+
+    #[test]
+    fn controller_test() {
+        for sim in [Simulator::new(16, 2), Simulator::new(16, 2).four_bit()].iter() {
+            let lcd = LcdDriver::from_bus(sim.clone(), 16, 2).unwrap();
+            lcd.print("Hello").unwrap();
+            lcd.set_cursor(1, 14).unwrap();
+            lcd.print("wrap°").unwrap();
+            let controller = sim.controller();
+            assert!(controller.is_display_on());
+            assert_eq!(
+                controller.lines('?'),
+                vec!["Hello           ", "              wr"]
+            );
+            assert_eq!(controller.glyph(Icon::BELL.index()), Icon::BELL.char_data());
+            assert_eq!(controller.glyph(b'-'), [0, 0, 0, 0x1F, 0, 0, 0, 0]);
+            assert_eq!(controller.cursor(), None);
+        }
+    }
+
+    #[test]
+    fn rom_test() {
+        let sim = Simulator::new(8, 1).with_rom(Rom::A02);
+        let lcd = LcdDriver::from_bus(sim.clone(), 8, 1)
+            .unwrap()
+            .with_rom(Rom::A02);
+        lcd.print("Größe~\\").unwrap();
+        let controller = sim.controller();
+        assert_eq!(controller.lines('?'), vec!["Größe~\\ "]);
+        assert_eq!(controller.code_at(0, 2), 0xF6);
+    }
+
+    #[test]
+    fn shift_test() {
+        let mut controller = Controller::new(4, 2);
+        for byte in [0x38, LCD_DISPLAY_CONTROL | LCD_DISPLAY_ON | LCD_CURSOR_ON].iter() {
+            controller.execute(*byte, false);
+        }
+        for byte in b"abcdef".iter() {
+            controller.execute(*byte, true);
+        }
+        assert_eq!(controller.lines('?'), vec!["abcd", "    "]);
+        controller.execute(LCD_CURSOR_SHIFT | LCD_DISPLAY_MOVE | LCD_LEFT, false);
+        controller.execute(LCD_CURSOR_SHIFT | LCD_DISPLAY_MOVE | LCD_LEFT, false);
+        assert_eq!(controller.lines('?'), vec!["cdef", "    "]);
+        assert_eq!(controller.cursor(), None);
+        controller.execute(LCD_CURSOR_SHIFT | LCD_CURSOR_MOVE | LCD_LEFT, false);
+        assert_eq!(controller.cursor(), Some((0, 3)));
+        // The end of the first line carries on at the start of the second
+        controller.execute(LCD_SET_DDRAM_ADDR | 39, false);
+        controller.execute(b'x', true);
+        controller.execute(b'y', true);
+        controller.execute(LCD_RETURN_HOME, false);
+        assert_eq!(controller.lines('?'), vec!["abcd", "y   "]);
+        assert_eq!(controller.code_at(1, 0), b'y');
+    }
+}
@@ -0,0 +1,333 @@
+use crate::sim::Controller;
+use gpio_cdev::errors::Error;
+use std::fmt::Write as FmtWrite;
+use std::fs::File;
+use std::io::{BufWriter, ErrorKind, Write};
+use std::path::Path;
+
+// A picture of the display at one moment, for docs and for comparing against known good images in
+// tests. Every pixel of every cell is kept, lit or not, with the gaps between cells in between
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    cols: u8,
+    rows: u8,
+    // One glyph per cell, row by row
+    cells: Vec<[u8; 8]>,
+    theme: Theme,
+    scale: u32,
+}
+
+// Colours as RGB
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub background: [u8; 3],
+    pub lit: [u8; 3],
+    // Pixels that are off still show faintly on most panels
+    pub unlit: [u8; 3],
+}
+
+// Blank pixels around the edge of the glass, in pixels of the display
+const MARGIN: u32 = 2;
+
+impl Theme {
+    // Dark pixels on a yellow green backlight
+    pub const GREEN: Theme = Theme {
+        background: [0x9C, 0xC2, 0x1E],
+        lit: [0x1C, 0x2C, 0x0C],
+        unlit: [0x8E, 0xB3, 0x1A],
+    };
+    // White pixels on a blue backlight
+    pub const BLUE: Theme = Theme {
+        background: [0x1A, 0x3F, 0xD8],
+        lit: [0xEC, 0xF2, 0xFF],
+        unlit: [0x24, 0x4B, 0xE2],
+    };
+    // Yellow pixels on black, like the character OLEDs
+    pub const OLED: Theme = Theme {
+        background: [0x00, 0x00, 0x00],
+        lit: [0xF4, 0xE0, 0x3C],
+        unlit: [0x0C, 0x0C, 0x0C],
+    };
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::GREEN
+    }
+}
+
+impl Snapshot {
+    pub fn new(controller: &Controller) -> Self {
+        let (cols, rows) = (controller.get_cols(), controller.get_rows());
+        Snapshot {
+            cols,
+            rows,
+            cells: (0..rows)
+                .flat_map(|row| (0..cols).map(move |col| controller.pixels(row, col)))
+                .collect(),
+            theme: Theme::default(),
+            scale: 4,
+        }
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    // How many pixels of the image each pixel of the display takes up on a side. From 3 up
+    // there's a line of background left between pixels
+    pub fn scale(mut self, scale: u32) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    // The size of the display in its own pixels, margin and gaps included. A display without
+    // cells is only its margin
+    pub fn dots(&self) -> (u32, u32) {
+        (
+            MARGIN * 2 + (self.cols as u32 * 6).saturating_sub(1),
+            MARGIN * 2 + (self.rows as u32 * 9).saturating_sub(1),
+        )
+    }
+
+    // `None` for the margin and the gaps between cells
+    pub fn dot(&self, x: u32, y: u32) -> Option<bool> {
+        let (width, height) = self.dots();
+        if x < MARGIN || y < MARGIN || x >= width - MARGIN || y >= height - MARGIN {
+            return None;
+        }
+        let (x, y) = (x - MARGIN, y - MARGIN);
+        if x % 6 == 5 || y % 9 == 8 {
+            return None;
+        }
+        let cell = self.cells[(y / 9 * self.cols as u32 + x / 6) as usize];
+        Some(cell[(y % 9) as usize] >> (4 - x % 6) & 1 != 0)
+    }
+
+    // Width and height of the image
+    pub fn size(&self) -> (u32, u32) {
+        let (width, height) = self.dots();
+        (width * self.scale, height * self.scale)
+    }
+
+    pub fn to_svg(&self) -> String {
+        let (width, height) = self.size();
+        let (dots_wide, dots_high) = self.dots();
+        let side = self.pixel_side();
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+             viewBox=\"0 0 {} {}\">\n<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+            width,
+            height,
+            width,
+            height,
+            width,
+            height,
+            hex(self.theme.background)
+        );
+        for y in 0..dots_high {
+            for x in 0..dots_wide {
+                if let Some(lit) = self.dot(x, y) {
+                    let colour = if lit {
+                        self.theme.lit
+                    } else {
+                        self.theme.unlit
+                    };
+                    let _ = writeln!(
+                        svg,
+                        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                        x * self.scale,
+                        y * self.scale,
+                        side,
+                        side,
+                        hex(colour)
+                    );
+                }
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    // The image as 8 bit RGB, row by row
+    pub fn to_rgb(&self) -> Vec<u8> {
+        let (width, height) = self.size();
+        let side = self.pixel_side();
+        let mut image = Vec::with_capacity((width * height * 3) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let inside = x % self.scale < side && y % self.scale < side;
+                let colour = match self.dot(x / self.scale, y / self.scale) {
+                    Some(true) if inside => self.theme.lit,
+                    Some(false) if inside => self.theme.unlit,
+                    _ => self.theme.background,
+                };
+                image.extend_from_slice(&colour);
+            }
+        }
+        image
+    }
+
+    #[cfg(feature = "png")]
+    pub fn write_png<W: Write>(&self, out: W) -> Result<(), Error> {
+        let (width, height) = self.size();
+        let mut encoder = png::Encoder::new(out, width, height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(png_error)?;
+        writer.write_image_data(&self.to_rgb()).map_err(png_error)
+    }
+
+    // Picks the format from the extension, `.svg` or `.png` with the png feature
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("svg") => {
+                let mut out = BufWriter::new(File::create(path)?);
+                out.write_all(self.to_svg().as_bytes())?;
+                out.flush()?;
+                Ok(())
+            }
+            #[cfg(feature = "png")]
+            Some("png") => self.write_png(BufWriter::new(File::create(path)?)),
+            _ => Err(Error::from(std::io::Error::new(
+                ErrorKind::InvalidInput,
+                "Snapshots can only be saved as .svg, or .png with the png feature",
+            ))),
+        }
+    }
+
+    fn pixel_side(&self) -> u32 {
+        if self.scale >= 3 {
+            self.scale - 1
+        } else {
+            self.scale
+        }
+    }
+}
+
+fn hex(colour: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", colour[0], colour[1], colour[2])
+}
+
+#[cfg(feature = "png")]
+fn png_error(error: png::EncodingError) -> Error {
+    Error::from(std::io::Error::other(error))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::icons::Icon;
+    use crate::lcd::LcdDriver;
+    use crate::scheduler::Job;
+    use crate::sim::Simulator;
+    use crate::snapshot::*;
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+
+    // The lit pixels of the first cell row, a line of text per row of pixels
+    fn top_row(snapshot: &Snapshot) -> Vec<String> {
+        let (width, _) = snapshot.dots();
+        (MARGIN..MARGIN + 8)
+            .map(|y| {
+                (MARGIN..width - MARGIN)
+                    .map(|x| match snapshot.dot(x, y) {
+                        Some(true) => '#',
+                        Some(false) => '.',
+                        None => ' ',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn snapshot_test() {
+        let simulator = Simulator::new(2, 1);
+        let lcd = LcdDriver::from_bus(simulator.clone(), 2, 1).unwrap();
+        lcd.write(b'T').unwrap();
+        lcd.write(Icon::BELL.index()).unwrap();
+        let snapshot = Snapshot::new(&simulator.controller()).theme(Theme::OLED);
+        assert_eq!(snapshot.dots(), (15, 12));
+        assert_eq!(snapshot.size(), (60, 48));
+        assert_eq!(
+            top_row(&snapshot),
+            vec![
+                "##### .....",
+                "..#.. ..#..",
+                "..#.. .#.#.",
+                "..#.. .#.#.",
+                "..#.. #...#",
+                "..#.. #...#",
+                "..#.. #####",
+                "..... ..#..",
+            ]
+        );
+        let svg = snapshot.to_svg();
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(svg.matches("fill=\"#f4e03c\"").count(), 11 + 15);
+        let rgb = snapshot.to_rgb();
+        assert_eq!(rgb.len(), 60 * 48 * 3);
+        // The top left pixel of the T, then the line of background left beside it
+        let at = |x: usize, y: usize| &rgb[(y * 60 + x) * 3..(y * 60 + x) * 3 + 3];
+        assert_eq!(at(8, 8), &Theme::OLED.lit);
+        assert_eq!(at(11, 8), &Theme::OLED.background);
+    }
+
+    #[test]
+    fn empty_test() {
+        let snapshot = Snapshot::new(&Simulator::new(0, 2).controller());
+        assert_eq!(snapshot.dots(), (MARGIN * 2, MARGIN * 2 + 17));
+        assert_eq!(snapshot.dot(MARGIN, MARGIN), None);
+        // Only the background
+        assert_eq!(snapshot.to_svg().matches("<rect").count(), 1);
+        let (width, height) = snapshot.size();
+        assert_eq!(snapshot.to_rgb().len(), (width * height * 3) as usize);
+        let snapshot = Snapshot::new(&Simulator::new(16, 0).controller());
+        assert_eq!(snapshot.dots(), (MARGIN * 2 + 95, MARGIN * 2));
+        assert!(top_row(&snapshot).iter().all(|line| line.trim().is_empty()));
+    }
+
+    #[test]
+    fn scroll_frames_test() {
+        let simulator = Simulator::new(4, 1);
+        let lcd = Arc::new(Mutex::new(
+            LcdDriver::from_bus(simulator.clone(), 4, 1).unwrap(),
+        ));
+        let mut job = Job::new("abcdef", 0, None);
+        let mut frames = Vec::new();
+        for _ in 0..3 {
+            job.run(Arc::clone(&lcd)).unwrap();
+            frames.push(top_row(&Snapshot::new(&simulator.controller()))[2].clone());
+        }
+        // Each run moves the text along a column
+        assert_eq!(
+            frames,
+            vec![
+                ".###. #.##. .###. .##.#",
+                "#.##. .###. .##.# .###.",
+                ".###. .##.# .###. .#...",
+            ]
+        );
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn png_test() {
+        let simulator = Simulator::new(16, 2);
+        LcdDriver::from_bus(simulator.clone(), 16, 2)
+            .unwrap()
+            .print("png")
+            .unwrap();
+        let mut png = Vec::new();
+        Snapshot::new(&simulator.controller())
+            .theme(Theme::BLUE)
+            .scale(2)
+            .write_png(&mut png)
+            .unwrap();
+        assert_eq!(&png[1..4], b"PNG");
+        // The width and height in the header
+        assert_eq!(&png[16..24], &[0, 0, 0, 198, 0, 0, 0, 42]);
+    }
+}
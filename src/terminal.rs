@@ -0,0 +1,236 @@
+use crate::hd44780::Bus;
+use crate::sim::{Controller, Simulator};
+use gpio_cdev::errors::Error;
+use std::io::{self, Stdout, Write};
+
+// Shows a simulated display on a terminal, redrawn whenever a command changes what's on it. Pass
+// it to `LcdDriver::from_bus` in place of the GPIO lines and everything built on the driver,
+// `ThreadedLcd` jobs included, runs against it unchanged
+pub struct TerminalBus<W> {
+    simulator: Simulator,
+    out: W,
+    rendering: Rendering,
+    // What was last drawn, so commands that don't change anything aren't redrawn
+    shown: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rendering {
+    // A character per cell, CGRAM glyphs and ROM characters Unicode doesn't have are drawn as
+    // braille
+    Text,
+    // Every pixel, with half blocks for two rows of pixels per line of the terminal
+    Pixels,
+}
+
+impl TerminalBus<Stdout> {
+    pub fn stdout(cols: u8, rows: u8) -> Self {
+        TerminalBus::new(Simulator::new(cols, rows), io::stdout())
+    }
+}
+
+impl<W: Write> TerminalBus<W> {
+    pub fn new(simulator: Simulator, out: W) -> Self {
+        TerminalBus {
+            simulator,
+            out,
+            rendering: Rendering::Text,
+            shown: None,
+        }
+    }
+
+    pub fn with_rendering(mut self, rendering: Rendering) -> Self {
+        self.rendering = rendering;
+        self
+    }
+
+    // Clone it to keep looking at the display after the bus has gone to the driver
+    pub fn simulator(&self) -> &Simulator {
+        &self.simulator
+    }
+
+    // Draws over the last frame from the top left of the terminal, clearing it the first time
+    pub fn refresh(&mut self) -> io::Result<()> {
+        let frame = match self.rendering {
+            Rendering::Text => render_text(&self.simulator.controller()),
+            Rendering::Pixels => render_pixels(&self.simulator.controller()),
+        };
+        if self.shown.as_ref() == Some(&frame) {
+            return Ok(());
+        }
+        if self.shown.is_none() {
+            write!(self.out, "\x1b[2J")?;
+        }
+        write!(self.out, "\x1b[H{}", frame)?;
+        self.out.flush()?;
+        self.shown = Some(frame);
+        Ok(())
+    }
+}
+
+impl<W: Write> Bus for TerminalBus<W> {
+    type Error = Error;
+
+    fn is_eight_bit(&self) -> bool {
+        self.simulator.is_eight_bit()
+    }
+
+    fn write_bits(&mut self, bits: u8, data: bool) -> Result<(), Error> {
+        self.simulator.write_bits(bits, data)?;
+        self.refresh().map_err(Error::from)
+    }
+
+    fn delay_us(&mut self, _us: u32) {}
+}
+
+// The display in a box drawing frame. The cursor is underlined, or shown inverted when it blinks
+pub fn render_text(controller: &Controller) -> String {
+    let cols = controller.get_cols() as usize;
+    let mut out = format!("┌{}┐\n", "─".repeat(cols));
+    for (row, line) in controller.lines('\0').iter().enumerate() {
+        out.push('│');
+        for (col, c) in line.chars().enumerate() {
+            let c = match c {
+                '\0' => braille(controller.glyph(controller.code_at(row as u8, col as u8))),
+                c => c,
+            };
+            match cursor_at(controller, row, col) {
+                Some(true) => out.push_str(&format!("\x1b[7m{}\x1b[27m", c)),
+                Some(false) => out.push_str(&format!("\x1b[4m{}\x1b[24m", c)),
+                None => out.push(c),
+            }
+        }
+        out.push_str("│\n");
+    }
+    out.push_str(&format!("└{}┘\n", "─".repeat(cols)));
+    out
+}
+
+// Every pixel of the display, with a blank column and line between cells like the real thing
+pub fn render_pixels(controller: &Controller) -> String {
+    let (cols, rows) = (
+        controller.get_cols() as usize,
+        controller.get_rows() as usize,
+    );
+    let width = (cols * 6).saturating_sub(1);
+    let mut out = format!("┌{}┐\n", "─".repeat(width));
+    for row in 0..rows {
+        let glyphs: Vec<[u8; 8]> = (0..cols)
+            .map(|col| controller.pixels(row as u8, col as u8))
+            .collect();
+        for pair in 0..4 {
+            out.push('│');
+            for (col, glyph) in glyphs.iter().enumerate() {
+                if col > 0 {
+                    out.push(' ');
+                }
+                for bit in (0..5).rev() {
+                    let top = glyph[pair * 2] >> bit & 1 != 0;
+                    let bottom = glyph[pair * 2 + 1] >> bit & 1 != 0;
+                    out.push(match (top, bottom) {
+                        (true, true) => '█',
+                        (true, false) => '▀',
+                        (false, true) => '▄',
+                        (false, false) => ' ',
+                    });
+                }
+            }
+            out.push_str("│\n");
+        }
+        if row + 1 < rows {
+            out.push_str(&format!("│{}│\n", " ".repeat(width)));
+        }
+    }
+    out.push_str(&format!("└{}┘\n", "─".repeat(width)));
+    out
+}
+
+// `Some(blinking)` if the cursor is on this cell
+fn cursor_at(controller: &Controller, row: usize, col: usize) -> Option<bool> {
+    match controller.cursor() {
+        Some(cursor) if cursor == (row as u8, col as u8) => Some(controller.is_blinking()),
+        _ => None,
+    }
+}
+
+// A glyph squeezed into the 2x4 dots of a braille character, the middle column of pixels goes to
+// both columns of dots
+fn braille(glyph: [u8; 8]) -> char {
+    // The bit for each dot, top to bottom for the left then the right column
+    const DOTS: [[u32; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+    let mut bits = 0;
+    for (dot_row, lines) in glyph.chunks(2).enumerate() {
+        let lit = lines[0] | lines[1];
+        if lit & 0x1C != 0 {
+            bits |= DOTS[0][dot_row];
+        }
+        if lit & 0x07 != 0 {
+            bits |= DOTS[1][dot_row];
+        }
+    }
+    std::char::from_u32(0x2800 + bits).unwrap_or(' ')
+}
+
+#[cfg(test)]
+mod test {
+    use crate::icons::Icon;
+    use crate::lcd::LcdDriver;
+    use crate::scheduler::{Job, ThreadedLcd};
+    use crate::terminal::*;
+    use std::sync::{Arc, Mutex};
+    use std::thread::sleep;
+    use std::time::{Duration, Instant};
+
+    // Collects the output so it can be looked at while the driver still owns the bus
+    #[derive(Clone, Default)]
+    struct Output(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn render_test() {
+        let simulator = Simulator::new(4, 2);
+        let lcd = LcdDriver::from_bus(simulator.clone(), 4, 2).unwrap();
+        lcd.print("ab").unwrap();
+        lcd.write(Icon::PAUSE.index()).unwrap();
+        assert_eq!(
+            render_text(&simulator.controller()),
+            "┌────┐\n│ab⣿ │\n│    │\n└────┘\n"
+        );
+        lcd.command(0x0E).unwrap();
+        let pixels = render_pixels(&simulator.controller());
+        let lines: Vec<&str> = pixels.lines().collect();
+        assert_eq!(lines.len(), 2 * 4 + 3);
+        assert_eq!(lines[1], "│      █     ██ ██      │");
+        assert_eq!(lines[2], "│ ▀▀▀▄ █▄▀▀▄ ██ ██      │");
+        // The underline cursor sits on the last row of pixels of the fourth cell
+        assert_eq!(lines[4], "│ ▀▀▀▀ ▀▀▀▀  ██ ██ ▄▄▄▄▄│");
+    }
+
+    #[test]
+    fn threaded_test() {
+        let output = Output::default();
+        let bus = TerminalBus::new(Simulator::new(16, 2), output.clone());
+        let simulator = bus.simulator().clone();
+        let lcd = ThreadedLcd::with_driver(LcdDriver::from_bus(bus, 16, 2).unwrap());
+        lcd.add_job(Job::new("terminal", 1, None));
+        // The frame is written after the simulator has the line, so waiting on it covers both
+        let shown = || String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !shown().contains("│terminal        │") {
+            assert!(Instant::now() < deadline, "the job was never drawn");
+            sleep(Duration::from_millis(1));
+        }
+        assert_eq!(simulator.controller().lines('?')[1], "terminal        ");
+        assert!(shown().starts_with("\x1b[2J\x1b[H┌"));
+    }
+}
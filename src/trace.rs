@@ -0,0 +1,456 @@
+use crate::hd44780::*;
+use crate::sim::Interface;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// Wraps any bus and keeps a record of everything sent through it, without changing what's sent.
+// Each byte is decoded into the operation it asks of the controller, and can be written out as it
+// happens. The pins' levels over time can be exported as a VCD file for GTKWave
+pub struct TraceBus<B> {
+    bus: B,
+    trace: Trace,
+    interface: Interface,
+    log: Option<Box<dyn Write + Send>>,
+}
+
+// The record a `TraceBus` keeps, clones share the same record so keep one to look at it
+#[derive(Clone)]
+pub struct Trace {
+    inner: Arc<Mutex<TraceLog>>,
+}
+
+struct TraceLog {
+    eight_bit_bus: bool,
+    started: Instant,
+    // When the next transfer can start, the bus's own delays count towards this
+    time_us: u64,
+    limit: usize,
+    transfers: VecDeque<Transfer>,
+    transactions: VecDeque<Transaction>,
+}
+
+// One write to the bus, a byte or a nibble, as it would appear on the pins
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transfer {
+    // Microseconds from the start of the trace
+    pub at_us: u64,
+    pub bits: u8,
+    pub data: bool,
+}
+
+// A whole byte received by the controller
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transaction {
+    pub at: Duration,
+    pub byte: u8,
+    pub data: bool,
+}
+
+// What a byte asks the controller to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Clear,
+    Home,
+    EntryMode { increment: bool, shift: bool },
+    Display { on: bool, cursor: bool, blink: bool },
+    Shift { display: bool, right: bool },
+    FunctionSet { eight_bit: bool, two_line: bool },
+    SetCgram(u8),
+    SetDdram(u8),
+    Write(u8),
+    // 0x00, which isn't an instruction
+    Unknown(u8),
+}
+
+// The most transfers and transactions kept, older ones are dropped first
+const DEFAULT_LIMIT: usize = 100_000;
+
+// How PinBus pulses the enable line, in microseconds from setting the other lines
+const ENABLE_HIGH_US: u64 = 10;
+const ENABLE_LOW_US: u64 = 20;
+const TRANSFER_US: u64 = 120;
+
+impl<B: Bus> TraceBus<B> {
+    pub fn new(bus: B) -> Self {
+        let trace = Trace {
+            inner: Arc::new(Mutex::new(TraceLog {
+                eight_bit_bus: bus.is_eight_bit(),
+                started: Instant::now(),
+                time_us: 0,
+                limit: DEFAULT_LIMIT,
+                transfers: VecDeque::new(),
+                transactions: VecDeque::new(),
+            })),
+        };
+        TraceBus {
+            interface: Interface::settled(bus.is_eight_bit()),
+            bus,
+            trace,
+            log: None,
+        }
+    }
+
+    // Also write each operation out as a line as it's sent, like `0.004512 SET_DDRAM 0x40`
+    pub fn with_log<W: Write + Send + 'static>(mut self, log: W) -> Self {
+        self.log = Some(Box::new(log));
+        self
+    }
+
+    // Keep at most this many transfers and transactions
+    pub fn with_limit(self, limit: usize) -> Self {
+        self.trace.inner.lock().limit = limit.max(1);
+        self
+    }
+
+    pub fn trace(&self) -> &Trace {
+        &self.trace
+    }
+
+    pub fn into_inner(self) -> B {
+        self.bus
+    }
+}
+
+impl<B: Bus> Bus for TraceBus<B> {
+    type Error = B::Error;
+
+    fn is_eight_bit(&self) -> bool {
+        self.bus.is_eight_bit()
+    }
+
+    fn write_bits(&mut self, bits: u8, data: bool) -> Result<(), B::Error> {
+        let eight_bit_bus = self.bus.is_eight_bit();
+        // Recorded before it's sent, so a write that fails shows up as the last thing tried
+        let transaction = {
+            let mut log = self.trace.inner.lock();
+            let at_us = log.next_time();
+            log.push_transfer(Transfer { at_us, bits, data });
+            let byte = self.interface.receive(bits, data, eight_bit_bus);
+            byte.map(|byte| {
+                let transaction = Transaction {
+                    at: Duration::from_micros(at_us),
+                    byte,
+                    data,
+                };
+                log.push_transaction(transaction);
+                transaction
+            })
+        };
+        if let (Some(transaction), Some(log)) = (transaction, self.log.as_mut()) {
+            // The trace is there to help, so a log that can't be written isn't worth failing over
+            let _ = writeln!(log, "{}", transaction);
+        }
+        self.bus.write_bits(bits, data)
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        self.trace.inner.lock().time_us += us as u64;
+        self.bus.delay_us(us);
+    }
+
+    fn is_busy(&mut self) -> Result<Option<bool>, B::Error> {
+        self.bus.is_busy()
+    }
+}
+
+impl TraceLog {
+    // Transfers are spaced out at least as far as PinBus would, so they don't overlap when the
+    // bus underneath doesn't take any time
+    fn next_time(&mut self) -> u64 {
+        let elapsed = self.started.elapsed().as_micros() as u64;
+        let at_us = self.time_us.max(elapsed);
+        self.time_us = at_us + TRANSFER_US;
+        at_us
+    }
+
+    fn push_transfer(&mut self, transfer: Transfer) {
+        if self.transfers.len() == self.limit {
+            self.transfers.pop_front();
+        }
+        self.transfers.push_back(transfer);
+    }
+
+    fn push_transaction(&mut self, transaction: Transaction) {
+        if self.transactions.len() == self.limit {
+            self.transactions.pop_front();
+        }
+        self.transactions.push_back(transaction);
+    }
+}
+
+impl Trace {
+    pub fn transfers(&self) -> Vec<Transfer> {
+        self.inner.lock().transfers.iter().cloned().collect()
+    }
+
+    pub fn transactions(&self) -> Vec<Transaction> {
+        self.inner.lock().transactions.iter().cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        let mut log = self.inner.lock();
+        log.transfers.clear();
+        log.transactions.clear();
+    }
+
+    // RS, E and the data lines the bus has, as a value change dump with a 1us timescale
+    pub fn write_vcd<W: Write>(&self, mut out: W) -> io::Result<()> {
+        let (eight_bit_bus, transfers) = {
+            let log = self.inner.lock();
+            let transfers: Vec<Transfer> = log.transfers.iter().cloned().collect();
+            (log.eight_bit_bus, transfers)
+        };
+        let data_lines: Vec<u8> = if eight_bit_bus {
+            (0..8).collect()
+        } else {
+            (4..8).collect()
+        };
+        writeln!(out, "$version gpio_lcd {} $end", env!("CARGO_PKG_VERSION"))?;
+        writeln!(out, "$timescale 1us $end")?;
+        writeln!(out, "$scope module lcd $end")?;
+        writeln!(out, "$var wire 1 r rs $end")?;
+        writeln!(out, "$var wire 1 e e $end")?;
+        for line in data_lines.iter() {
+            writeln!(out, "$var wire 1 d{} d{} $end", line, line)?;
+        }
+        writeln!(out, "$upscope $end")?;
+        writeln!(out, "$enddefinitions $end")?;
+
+        // The level of every line, RS then E then the data lines
+        let mut levels = vec![false; data_lines.len() + 2];
+        let mut ids = vec!["r".to_string(), "e".to_string()];
+        ids.extend(data_lines.iter().map(|line| format!("d{}", line)));
+        writeln!(out, "#0")?;
+        writeln!(out, "$dumpvars")?;
+        for id in ids.iter() {
+            writeln!(out, "0{}", id)?;
+        }
+        writeln!(out, "$end")?;
+        for transfer in transfers.iter() {
+            let mut next = levels.clone();
+            next[0] = transfer.data;
+            for (i, _) in data_lines.iter().enumerate() {
+                next[i + 2] = transfer.bits >> i & 1 != 0;
+            }
+            let changes: Vec<usize> = (0..levels.len())
+                .filter(|&i| next[i] != levels[i])
+                .collect();
+            if !changes.is_empty() {
+                // A transfer right at the start goes in with the initial values
+                if transfer.at_us > 0 {
+                    writeln!(out, "#{}", transfer.at_us)?;
+                }
+                for i in changes {
+                    writeln!(out, "{}{}", next[i] as u8, ids[i])?;
+                }
+            }
+            writeln!(out, "#{}", transfer.at_us + ENABLE_HIGH_US)?;
+            writeln!(out, "1e")?;
+            writeln!(out, "#{}", transfer.at_us + ENABLE_LOW_US)?;
+            writeln!(out, "0e")?;
+            levels = next;
+        }
+        Ok(())
+    }
+
+    pub fn save_vcd<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write_vcd(&mut out)?;
+        out.flush()
+    }
+}
+
+impl fmt::Debug for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let log = self.inner.lock();
+        f.debug_struct("Trace")
+            .field("transfers", &log.transfers.len())
+            .field("transactions", &log.transactions.len())
+            .finish()
+    }
+}
+
+impl Transaction {
+    pub fn op(&self) -> Op {
+        Op::decode(self.byte, self.data)
+    }
+}
+
+impl fmt::Display for Transaction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.6} {}", self.at.as_secs_f64(), self.op())
+    }
+}
+
+impl Op {
+    pub fn decode(byte: u8, data: bool) -> Self {
+        if data {
+            Op::Write(byte)
+        } else if byte & LCD_SET_DDRAM_ADDR != 0 {
+            Op::SetDdram(byte & 0x7F)
+        } else if byte & LCD_SET_CGRAM_ADDR != 0 {
+            Op::SetCgram(byte & 0x3F)
+        } else if byte & LCD_FUNCTION_SET != 0 {
+            Op::FunctionSet {
+                eight_bit: byte & LCD_8BITMODE != 0,
+                two_line: byte & LCD_2LINE != 0,
+            }
+        } else if byte & LCD_CURSOR_SHIFT != 0 {
+            Op::Shift {
+                display: byte & LCD_DISPLAY_MOVE != 0,
+                right: byte & LCD_RIGHT != 0,
+            }
+        } else if byte & LCD_DISPLAY_CONTROL != 0 {
+            Op::Display {
+                on: byte & LCD_DISPLAY_ON != 0,
+                cursor: byte & LCD_CURSOR_ON != 0,
+                blink: byte & LCD_BLINK_ON != 0,
+            }
+        } else if byte & LCD_ENTRY_MODE_SET != 0 {
+            Op::EntryMode {
+                increment: byte & LCD_ENTRY_LEFT != 0,
+                shift: byte & 0x01 != 0,
+            }
+        } else if byte & LCD_RETURN_HOME != 0 {
+            Op::Home
+        } else if byte & LCD_CLEAR_DISPLAY != 0 {
+            Op::Clear
+        } else {
+            Op::Unknown(byte)
+        }
+    }
+}
+
+// In the datasheet's names, like `SET_DDRAM 0x40` or `WRITE 'A'`
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn flag(on: bool, name: &str) -> &str {
+            if on {
+                name
+            } else {
+                ""
+            }
+        }
+
+        match *self {
+            Op::Clear => write!(f, "CLEAR"),
+            Op::Home => write!(f, "HOME"),
+            Op::EntryMode { increment, shift } => write!(
+                f,
+                "ENTRY_MODE {}{}",
+                if increment { "increment" } else { "decrement" },
+                flag(shift, " shift")
+            ),
+            Op::Display { on, cursor, blink } => write!(
+                f,
+                "DISPLAY {}{}{}",
+                if on { "on" } else { "off" },
+                flag(cursor, " cursor"),
+                flag(blink, " blink")
+            ),
+            Op::Shift { display, right } => write!(
+                f,
+                "{} {}",
+                if display {
+                    "DISPLAY_SHIFT"
+                } else {
+                    "CURSOR_SHIFT"
+                },
+                if right { "right" } else { "left" }
+            ),
+            Op::FunctionSet {
+                eight_bit,
+                two_line,
+            } => write!(
+                f,
+                "FUNCTION_SET {} {}",
+                if eight_bit { "8bit" } else { "4bit" },
+                if two_line { "2line" } else { "1line" }
+            ),
+            Op::SetCgram(address) => write!(f, "SET_CGRAM 0x{:02X}", address),
+            Op::SetDdram(address) => write!(f, "SET_DDRAM 0x{:02X}", address),
+            Op::Unknown(byte) => write!(f, "UNKNOWN 0x{:02X}", byte),
+            Op::Write(byte) => match decode(byte) {
+                Some(c) => write!(f, "WRITE {:?}", c),
+                None => write!(f, "WRITE 0x{:02X}", byte),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::lcd::LcdDriver;
+    use crate::sim::Simulator;
+    use crate::trace::*;
+
+    #[test]
+    fn op_test() {
+        let ops: Vec<String> = [
+            (0x01, false),
+            (0x06, false),
+            (0x0C, false),
+            (0x18, false),
+            (0x28, false),
+            (0x48, false),
+            (0xC0, false),
+            (b'A', true),
+            (0xDF, true),
+            (0x02, true),
+        ]
+        .iter()
+        .map(|&(byte, data)| Op::decode(byte, data).to_string())
+        .collect();
+        assert_eq!(
+            ops,
+            vec![
+                "CLEAR",
+                "ENTRY_MODE increment",
+                "DISPLAY on",
+                "DISPLAY_SHIFT left",
+                "FUNCTION_SET 4bit 2line",
+                "SET_CGRAM 0x08",
+                "SET_DDRAM 0x40",
+                "WRITE 'A'",
+                "WRITE '°'",
+                "WRITE 0x02",
+            ]
+        );
+    }
+
+    #[test]
+    fn trace_test() {
+        let simulator = Simulator::new(16, 2).four_bit();
+        let bus = TraceBus::new(simulator.clone());
+        let trace = bus.trace().clone();
+        let lcd = LcdDriver::from_bus(bus, 16, 2).unwrap();
+        trace.clear();
+        lcd.set_cursor(1, 0).unwrap();
+        lcd.print("Hi").unwrap();
+        // The display behind the trace still gets everything
+        assert_eq!(simulator.controller().lines('?')[1], "Hi              ");
+        let ops: Vec<String> = trace
+            .transactions()
+            .iter()
+            .map(|transaction| transaction.op().to_string())
+            .collect();
+        assert_eq!(ops, vec!["SET_DDRAM 0x40", "WRITE 'H'", "WRITE 'i'"]);
+        // Two nibbles a byte on a 4 bit bus
+        assert_eq!(trace.transfers().len(), 6);
+
+        let mut vcd = Vec::new();
+        trace.write_vcd(&mut vcd).unwrap();
+        let vcd = String::from_utf8(vcd).unwrap();
+        assert!(vcd.contains("$var wire 1 d4 d4 $end"));
+        assert!(!vcd.contains("d0"));
+        // The high nibble of SET_DDRAM 0x40 (0xC) then the E pulse
+        let first = trace.transfers()[0].at_us;
+        assert!(vcd.contains(&format!("#{}\n1d6\n1d7\n#{}\n1e\n", first, first + 10)));
+    }
+}
@@ -0,0 +1,159 @@
+use crate::lcd::LcdDriver;
+use crate::region::Region;
+use crate::scheduler::Job;
+use gpio_cdev::errors::Error;
+
+// Both widgets keep their glyphs in CGRAM, so only one of them (or the icons) can be loaded at a
+// time. Their text is made of CGRAM character codes and can be handed straight to a `Job`
+
+const BLANK: char = ' ';
+
+#[derive(Debug, Clone, Copy)]
+pub struct ProgressBar {
+    width: u8,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BarGraph {
+    width: u8,
+    min: f32,
+    max: f32,
+}
+
+impl ProgressBar {
+    // Slots 0-4 hold a cell filled with 1-5 columns
+    const GLYPHS: [[u8; 8]; 5] = [
+        [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10],
+        [0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18],
+        [0x1C, 0x1C, 0x1C, 0x1C, 0x1C, 0x1C, 0x1C, 0x1C],
+        [0x1E, 0x1E, 0x1E, 0x1E, 0x1E, 0x1E, 0x1E, 0x1E],
+        [0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F],
+    ];
+
+    pub fn new(width: u8) -> Self {
+        ProgressBar { width }
+    }
+
+    pub fn width(&self) -> u8 {
+        self.width
+    }
+
+    pub fn load(lcd: &LcdDriver) -> Result<(), Error> {
+        for (loc, charmap) in ProgressBar::GLYPHS.iter().enumerate() {
+            lcd.create_char(loc as u8, *charmap)?;
+        }
+        Ok(())
+    }
+
+    // Text for a bar filled to `fraction` (0.0 - 1.0), with a resolution of 1/5 of a cell
+    pub fn text(&self, fraction: f32) -> String {
+        let total = self.width as usize * 5;
+        let filled = (fraction.clamp(0.0, 1.0) * total as f32).round() as usize;
+        (0..self.width as usize)
+            .map(|cell| match filled.saturating_sub(cell * 5).min(5) {
+                0 => BLANK,
+                columns => (columns as u8 - 1) as char,
+            })
+            .collect()
+    }
+
+    pub fn render(&self, lcd: &LcdDriver, fraction: f32, row: u8, col: u8) -> Result<(), Error> {
+        lcd.set_cursor(row, col)?;
+        lcd.print(self.text(fraction).as_str())
+    }
+
+    pub fn job(&self, fraction: f32, row: u8, col: u8) -> Job {
+        Job::new(self.text(fraction).as_str(), row, None)
+            .with_region(Region::new(row, col, self.width))
+    }
+}
+
+impl BarGraph {
+    // Slots 0-7 hold a cell filled with 1-8 rows from the bottom
+    const GLYPHS: [[u8; 8]; 8] = [
+        [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F],
+        [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F, 0x1F],
+        [0x00, 0x00, 0x00, 0x00, 0x00, 0x1F, 0x1F, 0x1F],
+        [0x00, 0x00, 0x00, 0x00, 0x1F, 0x1F, 0x1F, 0x1F],
+        [0x00, 0x00, 0x00, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F],
+        [0x00, 0x00, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F],
+        [0x00, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F],
+        [0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x1F],
+    ];
+
+    pub fn new(width: u8, min: f32, max: f32) -> Self {
+        BarGraph { width, min, max }
+    }
+
+    pub fn width(&self) -> u8 {
+        self.width
+    }
+
+    pub fn load(lcd: &LcdDriver) -> Result<(), Error> {
+        for (loc, charmap) in BarGraph::GLYPHS.iter().enumerate() {
+            lcd.create_char(loc as u8, *charmap)?;
+        }
+        Ok(())
+    }
+
+    // Height of a single bar, 0 is empty and 8 is a full cell
+    pub fn level(&self, value: f32) -> u8 {
+        if self.max <= self.min {
+            return 0;
+        }
+        let fraction = ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0);
+        (fraction * 8.0).round() as u8
+    }
+
+    // One bar per value, only the last `width` values are shown and they are right aligned so the
+    // graph reads like a sparkline
+    pub fn text(&self, values: &[f32]) -> String {
+        let shown = &values[values.len().saturating_sub(self.width as usize)..];
+        let mut text: String = (shown.len()..self.width as usize).map(|_| BLANK).collect();
+        for value in shown {
+            text.push(match self.level(*value) {
+                0 => BLANK,
+                level => (level - 1) as char,
+            });
+        }
+        text
+    }
+
+    pub fn render(&self, lcd: &LcdDriver, values: &[f32], row: u8, col: u8) -> Result<(), Error> {
+        lcd.set_cursor(row, col)?;
+        lcd.print(self.text(values).as_str())
+    }
+
+    pub fn job(&self, values: &[f32], row: u8, col: u8) -> Job {
+        Job::new(self.text(values).as_str(), row, None)
+            .with_region(Region::new(row, col, self.width))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::widgets::*;
+
+    #[test]
+    fn progress_bar_text_test() {
+        let bar = ProgressBar::new(4);
+        assert_eq!(bar.text(0.0), "    ");
+        assert_eq!(bar.text(0.05), "\u{0}   ");
+        assert_eq!(bar.text(0.5), "\u{4}\u{4}  ");
+        assert_eq!(bar.text(0.6), "\u{4}\u{4}\u{1} ");
+        assert_eq!(bar.text(1.0), "\u{4}\u{4}\u{4}\u{4}");
+        assert_eq!(bar.text(2.0), bar.text(1.0));
+    }
+
+    #[test]
+    fn bar_graph_text_test() {
+        let graph = BarGraph::new(4, 0.0, 8.0);
+        assert_eq!(graph.text(&[]), "    ");
+        assert_eq!(graph.text(&[8.0, 1.0]), "  \u{7}\u{0}");
+        assert_eq!(
+            graph.text(&[0.0, 2.0, 4.0, 6.0, 8.0]),
+            "\u{1}\u{3}\u{5}\u{7}"
+        );
+        assert_eq!(graph.text(&[-1.0, 100.0]), "   \u{7}");
+    }
+}